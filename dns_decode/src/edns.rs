@@ -0,0 +1,202 @@
+use dns_types::edns::*;
+use nom::{
+    bytes::complete::take,
+    combinator::{all_consuming, map, rest, verify},
+    multi::{length_data, many0},
+    number::complete::{be_u16, be_u8},
+    IResult,
+};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use crate::{error::DecodeError, input::DnsFrameInput};
+
+fn client_subnet(input: DnsFrameInput) -> IResult<DnsFrameInput, EdnsOption, DecodeError> {
+    let (input, family) = verify(be_u16, |family| *family == 1 || *family == 2)(input)?;
+    let max_prefix_length = if family == 1 { 32 } else { 128 };
+    let (input, source_prefix_length) = verify(be_u8, |len| *len <= max_prefix_length)(input)?;
+    let (input, scope_prefix_length) = verify(be_u8, |len| *len <= max_prefix_length)(input)?;
+    // the address is truncated to the octets covered by the source prefix
+    let (input, address) = verify(rest, |address: &DnsFrameInput| {
+        address.input.len() == (source_prefix_length as usize).div_ceil(8)
+    })(input)?;
+    let address = if family == 1 {
+        let mut octets = [0u8; 4];
+        octets[..address.input.len()].copy_from_slice(address.input);
+        IpAddr::V4(Ipv4Addr::from(octets))
+    } else {
+        let mut octets = [0u8; 16];
+        octets[..address.input.len()].copy_from_slice(address.input);
+        IpAddr::V6(Ipv6Addr::from(octets))
+    };
+    Ok((
+        input,
+        EdnsOption::ClientSubnet(ClientSubnet {
+            source_prefix_length,
+            scope_prefix_length,
+            address,
+        }),
+    ))
+}
+
+fn cookie(input: DnsFrameInput) -> IResult<DnsFrameInput, EdnsOption, DecodeError> {
+    let (input, client) = take(8usize)(input)?;
+    let (input, server) = verify(rest, |server: &DnsFrameInput| {
+        server.input.is_empty() || (8..=32).contains(&server.input.len())
+    })(input)?;
+    let mut client_cookie = [0u8; 8];
+    client_cookie.copy_from_slice(client.input);
+    Ok((
+        input,
+        EdnsOption::Cookie(Cookie {
+            client: client_cookie,
+            server: (!server.input.is_empty()).then(|| server.input.to_vec()),
+        }),
+    ))
+}
+
+fn extended_error(input: DnsFrameInput) -> IResult<DnsFrameInput, EdnsOption, DecodeError> {
+    let (input, info_code) = be_u16(input)?;
+    // the text should be UTF-8, keep the info code of a server that gets it wrong
+    let (input, extra_text) = map(rest, |extra_text: DnsFrameInput| {
+        String::from_utf8_lossy(extra_text.input).into_owned()
+    })(input)?;
+    Ok((
+        input,
+        EdnsOption::ExtendedError(ExtendedError {
+            info_code,
+            extra_text,
+        }),
+    ))
+}
+
+fn option_data(code: u16, data: DnsFrameInput) -> EdnsOption {
+    let option: IResult<DnsFrameInput, EdnsOption, DecodeError> = match code {
+        3 => map(rest, |nsid: DnsFrameInput| {
+            EdnsOption::Nsid(Nsid(nsid.input.to_vec()))
+        })(data),
+        8 => all_consuming(client_subnet)(data),
+        10 => all_consuming(cookie)(data),
+        12 => map(rest, |padding: DnsFrameInput| {
+            EdnsOption::Padding(Padding {
+                length: padding.input.len() as u16,
+            })
+        })(data),
+        15 => all_consuming(extended_error)(data),
+        _ => map(rest, |data: DnsFrameInput| EdnsOption::Unknown {
+            code,
+            data: data.input.to_vec(),
+        })(data),
+    };
+    // a malformed option is kept as raw bytes rather than failing the whole message
+    match option {
+        Ok((_, option)) => option,
+        Err(_) => EdnsOption::Unknown {
+            code,
+            data: data.input.to_vec(),
+        },
+    }
+}
+
+fn edns_option(input: DnsFrameInput) -> IResult<DnsFrameInput, EdnsOption, DecodeError> {
+    let (input, code) = be_u16(input)?;
+    let (input, data) = length_data(be_u16)(input)?;
+    Ok((input, option_data(code, data)))
+}
+
+pub fn edns_options(input: DnsFrameInput) -> IResult<DnsFrameInput, Vec<EdnsOption>, DecodeError> {
+    many0(edns_option)(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edns_options() {
+        let options_bytes = hex::decode(concat!(
+            "fde90002abcd",
+            "00030003677031",
+            "0008000700011800c00002",
+            "000a00100102030405060708a1a2a3a4a5a6a7a8",
+            "000c0003000000",
+            "000f00100012626c6f636b6564206279206f7073",
+            "000800050001200001",
+            "000f000400126fff",
+        ))
+        .unwrap();
+        let result = edns_options(DnsFrameInput::new(&options_bytes));
+
+        assert_eq!(
+            result,
+            Ok((
+                DnsFrameInput {
+                    frame: &options_bytes,
+                    input: &b""[..],
+                },
+                vec![
+                    EdnsOption::Unknown {
+                        code: 65001,
+                        data: vec![0xab, 0xcd],
+                    },
+                    EdnsOption::Nsid(Nsid(b"gp1".to_vec())),
+                    EdnsOption::ClientSubnet(ClientSubnet {
+                        source_prefix_length: 24,
+                        scope_prefix_length: 0,
+                        address: IpAddr::V4(Ipv4Addr::new(192, 0, 2, 0)),
+                    }),
+                    EdnsOption::Cookie(Cookie {
+                        client: [1, 2, 3, 4, 5, 6, 7, 8],
+                        server: Some(vec![0xa1, 0xa2, 0xa3, 0xa4, 0xa5, 0xa6, 0xa7, 0xa8]),
+                    }),
+                    EdnsOption::Padding(Padding { length: 3 }),
+                    EdnsOption::ExtendedError(ExtendedError {
+                        info_code: 18,
+                        extra_text: String::from("blocked by ops"),
+                    }),
+                    // client subnet whose address is longer than the source prefix
+                    EdnsOption::Unknown {
+                        code: 8,
+                        data: vec![0x00, 0x01, 0x20, 0x00, 0x01],
+                    },
+                    // extended error whose extra text is not UTF-8
+                    EdnsOption::ExtendedError(ExtendedError {
+                        info_code: 18,
+                        extra_text: String::from("o\u{fffd}"),
+                    }),
+                ]
+            ))
+        );
+    }
+
+    #[test]
+    fn test_edns_options_display() {
+        let options_bytes = hex::decode(concat!(
+            "00030003677031",
+            "0008000700011800c00002",
+            "000a00100102030405060708a1a2a3a4a5a6a7a8",
+            "000c0003000000",
+            "000f00100012626c6f636b6564206279206f7073",
+        ))
+        .unwrap();
+        let (_, options) = edns_options(DnsFrameInput::new(&options_bytes)).unwrap();
+        let edns = Edns {
+            udp_payload_size: 1232,
+            extended_rcode: 0,
+            version: 0,
+            dnssec_ok: true,
+            options,
+        };
+
+        assert_eq!(
+            edns.to_string(),
+            concat!(
+                "; EDNS: version: 0, flags: do; udp: 1232\n",
+                "; NSID: 677031 (\"gp1\")\n",
+                "; CLIENT-SUBNET: 192.0.2.0/24/0\n",
+                "; COOKIE: 0102030405060708a1a2a3a4a5a6a7a8\n",
+                "; PAD: (3 bytes)\n",
+                "; EDE: 18 (Prohibited): (blocked by ops)\n",
+            )
+        );
+    }
+}
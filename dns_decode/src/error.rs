@@ -0,0 +1,121 @@
+use nom::{
+    error::{Error, ErrorKind, FromExternalError, ParseError},
+    ErrorConvert,
+};
+use std::fmt::Display;
+use thiserror::Error;
+
+use crate::input::DnsFrameInput;
+
+#[derive(Error, Debug, PartialEq, Clone, Copy)]
+pub enum DecodeErrorKind {
+    #[error("message is shorter than the 12 byte header")]
+    TruncatedHeader,
+    #[error("unexpected end of message")]
+    Truncated,
+    #[error("invalid label length byte {0:#04x}")]
+    BadLabelLength(u8),
+    #[error("compression pointer to offset {0} is outside the message")]
+    PointerOutOfRange(usize),
+    #[error("compression pointer to offset {0} does not point backwards")]
+    ForwardPointer(usize),
+    #[error("compression pointers form a loop or chain too long")]
+    PointerLoop,
+    #[error("name exceeds 255 octets")]
+    NameTooLong,
+    #[error("rdata does not match its length of {0} bytes")]
+    RdataLengthMismatch(u16),
+    #[error("{0} unexpected bytes after the end of the message")]
+    TrailingBytes(usize),
+    #[error("malformed data ({0:?})")]
+    Malformed(ErrorKind),
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Section {
+    Header,
+    Question,
+    Answer,
+    Authority,
+    Additional,
+}
+
+impl Display for Section {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Section::Header => "header",
+            Section::Question => "question",
+            Section::Answer => "answer",
+            Section::Authority => "authority",
+            Section::Additional => "additional",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+#[derive(Error, Debug, PartialEq, Clone, Copy)]
+pub struct DecodeError {
+    pub kind: DecodeErrorKind,
+    /// Offset within the frame at which decoding failed
+    pub offset: usize,
+    /// Section being decoded, only known once the error reaches the message decoder
+    pub section: Option<Section>,
+    /// Index of the question or record within `section`
+    pub index: Option<usize>,
+}
+
+impl DecodeError {
+    pub fn new(kind: DecodeErrorKind, input: DnsFrameInput) -> Self {
+        Self {
+            kind,
+            offset: input.position(),
+            section: None,
+            index: None,
+        }
+    }
+
+    pub fn in_section(self, section: Section, index: Option<usize>) -> Self {
+        Self {
+            section: Some(section),
+            index,
+            ..self
+        }
+    }
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at offset {}", self.kind, self.offset)?;
+        match (self.section, self.index) {
+            (Some(section), Some(index)) => write!(f, " in {} record {}", section, index),
+            (Some(section), None) => write!(f, " in {}", section),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl<'a> ParseError<DnsFrameInput<'a>> for DecodeError {
+    fn from_error_kind(input: DnsFrameInput<'a>, kind: ErrorKind) -> Self {
+        let kind = match kind {
+            ErrorKind::Eof => DecodeErrorKind::Truncated,
+            kind => DecodeErrorKind::Malformed(kind),
+        };
+        DecodeError::new(kind, input)
+    }
+
+    fn append(_input: DnsFrameInput<'a>, _kind: ErrorKind, other: Self) -> Self {
+        other
+    }
+}
+
+impl<'a, E> FromExternalError<DnsFrameInput<'a>, E> for DecodeError {
+    fn from_external_error(input: DnsFrameInput<'a>, kind: ErrorKind, _e: E) -> Self {
+        DecodeError::from_error_kind(input, kind)
+    }
+}
+
+impl<'a> ErrorConvert<DecodeError> for Error<(DnsFrameInput<'a>, usize)> {
+    fn convert(self) -> DecodeError {
+        DecodeError::from_error_kind(self.input.0, self.code)
+    }
+}
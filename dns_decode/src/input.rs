@@ -21,10 +21,15 @@ pub struct DnsFrameInput<'a> {
 impl<'a> DnsFrameInput<'a> {
     pub fn new(frame: &'a [u8]) -> Self {
         Self {
-            frame: frame,
+            frame,
             input: frame,
         }
     }
+
+    /// Offset of the remaining input from the start of the frame
+    pub fn position(&self) -> usize {
+        (self.input.as_ptr() as usize).saturating_sub(self.frame.as_ptr() as usize)
+    }
 }
 
 // impl<'a> ParseError<&[u8]> for DnsFrameInput<'a> {
@@ -104,7 +109,7 @@ impl<'a, 'b> FindSubstring<&'b [u8]> for DnsFrameInput<'a> {
     }
 }
 
-impl<'a, 'b> FindToken<u8> for DnsFrameInput<'a> {
+impl<'a> FindToken<u8> for DnsFrameInput<'a> {
     fn find_token(&self, token: u8) -> bool {
         self.input.find_token(token)
     }
@@ -268,7 +273,7 @@ impl<'a> Slice<Range<usize>> for DnsFrameInput<'a> {
         let input = self.input.slice(range);
         DnsFrameInput {
             frame: self.frame,
-            input: input,
+            input,
         }
     }
 }
@@ -278,7 +283,7 @@ impl<'a> Slice<RangeTo<usize>> for DnsFrameInput<'a> {
         let input = self.input.slice(range);
         DnsFrameInput {
             frame: self.frame,
-            input: input,
+            input,
         }
     }
 }
@@ -288,7 +293,7 @@ impl<'a> Slice<RangeFrom<usize>> for DnsFrameInput<'a> {
         let input = self.input.slice(range);
         DnsFrameInput {
             frame: self.frame,
-            input: input,
+            input,
         }
     }
 }
@@ -298,7 +303,7 @@ impl<'a> Slice<RangeFull> for DnsFrameInput<'a> {
         let input = self.input.slice(range);
         DnsFrameInput {
             frame: self.frame,
-            input: input,
+            input,
         }
     }
 }
@@ -1,28 +1,67 @@
-use crate::{input::DnsFrameInput, message_header::*, query::*, resource_record::*};
+use crate::{
+    error::{DecodeError, DecodeErrorKind, Section},
+    input::DnsFrameInput,
+    message_header::*,
+    query::*,
+    resource_record::*,
+};
 use dns_types::*;
-use nom::{multi::count, IResult};
-use thiserror::Error;
-
-#[derive(Error, Debug)]
-pub enum DecodeError {
-    #[error("unknown parse error")]
-    Unknown,
-}
+use nom::{Err, IResult};
 
 pub fn decode_message(input: &[u8]) -> Result<Message, DecodeError> {
-    // TODO improve error reporting
-    let input = DnsFrameInput {
-        frame: input,
-        input: input,
-    };
-    let (_, m) = message(input).map_err(|_op| DecodeError::Unknown)?;
+    let input = DnsFrameInput::new(input);
+    let (input, m) = message(input).map_err(|e| match e {
+        Err::Error(e) | Err::Failure(e) => e,
+        Err::Incomplete(_) => DecodeError::new(DecodeErrorKind::Truncated, input),
+    })?;
+    if !input.input.is_empty() {
+        return Err(DecodeError::new(
+            DecodeErrorKind::TrailingBytes(input.input.len()),
+            input,
+        ));
+    }
     Ok(m)
 }
 
-fn message(input: DnsFrameInput) -> IResult<DnsFrameInput, Message> {
-    let (input, header) = message_header(input)?;
-    let (input, queries) = count(query, header.query_count as usize)(input)?;
-    let (input, answers) = count(resource_record, header.answer_count as usize)(input)?;
+/// Wire length of the smallest entry, a question for the root name
+const MIN_ENTRY_LENGTH: usize = 5;
+
+/// Decodes `count` consecutive entries of a section, recording the section and index of the
+/// entry in any error.
+fn section<'a, O>(
+    parser: fn(DnsFrameInput<'a>) -> IResult<DnsFrameInput<'a>, O, DecodeError>,
+    count: u16,
+    section: Section,
+) -> impl Fn(DnsFrameInput<'a>) -> IResult<DnsFrameInput<'a>, Vec<O>, DecodeError> {
+    move |mut input: DnsFrameInput<'a>| {
+        // the counts come from the message, so only reserve what its length could hold
+        let mut entries =
+            Vec::with_capacity((count as usize).min(input.input.len() / MIN_ENTRY_LENGTH));
+        for index in 0..count as usize {
+            let (remaining, entry) =
+                parser(input).map_err(|e| e.map(|e| e.in_section(section, Some(index))))?;
+            entries.push(entry);
+            input = remaining;
+        }
+        Ok((input, entries))
+    }
+}
+
+fn message(input: DnsFrameInput) -> IResult<DnsFrameInput, Message, DecodeError> {
+    let (input, header) =
+        message_header(input).map_err(|e| e.map(|e| e.in_section(Section::Header, None)))?;
+    let (input, queries) = section(query, header.query_count, Section::Question)(input)?;
+    let (input, answers) = section(resource_record, header.answer_count, Section::Answer)(input)?;
+    let (input, authorities) = section(
+        resource_record,
+        header.name_server_count,
+        Section::Authority,
+    )(input)?;
+    let (input, additionals) = section(
+        resource_record,
+        header.additional_count,
+        Section::Additional,
+    )(input)?;
 
     Ok((
         input,
@@ -30,6 +69,8 @@ fn message(input: DnsFrameInput) -> IResult<DnsFrameInput, Message> {
             header,
             queries,
             answers,
+            authorities,
+            additionals,
         },
     ))
 }
@@ -37,7 +78,7 @@ fn message(input: DnsFrameInput) -> IResult<DnsFrameInput, Message> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::net::Ipv4Addr;
+    use std::net::{Ipv4Addr, Ipv6Addr};
 
     #[test]
     fn test_message_request() {
@@ -62,6 +103,8 @@ mod tests {
                             truncated: Truncated::NotTruncated,
                             recursion_desired: RecursionDesired::Desired,
                             recursion_available: RecursionAvailable::NotAvailable,
+                            authentic_data: AuthenticData::NotAuthentic,
+                            checking_disabled: CheckingDisabled::NotDisabled,
                             rcode: Rcode::NoError,
                         },
                         query_count: 1,
@@ -70,11 +113,13 @@ mod tests {
                         additional_count: 0,
                     },
                     queries: vec![Query {
-                        name: Name(vec![String::from("example"), String::from("com")]),
+                        name: Name(vec![b"example".to_vec(), b"com".to_vec()]),
                         query_type: QueryType::A,
                         query_class: QueryClass::Internet,
                     }],
-                    answers: vec![]
+                    answers: vec![],
+                    authorities: vec![],
+                    additionals: vec![],
                 }
             ))
         );
@@ -105,6 +150,8 @@ mod tests {
                             truncated: Truncated::NotTruncated,
                             recursion_desired: RecursionDesired::Desired,
                             recursion_available: RecursionAvailable::Available,
+                            authentic_data: AuthenticData::NotAuthentic,
+                            checking_disabled: CheckingDisabled::NotDisabled,
                             rcode: Rcode::NoError,
                         },
                         query_count: 1,
@@ -113,19 +160,141 @@ mod tests {
                         additional_count: 0,
                     },
                     queries: vec![Query {
-                        name: Name(vec![String::from("example"), String::from("com")]),
+                        name: Name(vec![b"example".to_vec(), b"com".to_vec()]),
                         query_type: QueryType::A,
                         query_class: QueryClass::Internet,
                     }],
                     answers: vec![ResourceRecord {
-                        name: Name(vec![String::from("example"), String::from("com")]),
+                        name: Name(vec![b"example".to_vec(), b"com".to_vec()]),
                         resource_type: ResourceType::A,
                         resource_class: ResourceClass::Internet,
                         ttl: 23042,
                         rdata: ResourceData::A(Ipv4Addr::from(0x5db8d822))
-                    }]
+                    }],
+                    authorities: vec![],
+                    additionals: vec![],
                 }
             ))
         );
     }
+
+    #[test]
+    fn test_message_response_authority_and_additional() {
+        let message_bytes = hex::decode(
+            "123481800001000100010001076578616d706c6503636f6d0000010001c00c0001000100000e1000045db8d822026e73c00c0001000100000e100004c0000201c02d001c000100000e10001020010db8000000000000000000000001"
+        )
+        .unwrap();
+        let (input, message) = message(DnsFrameInput::new(&message_bytes)).unwrap();
+
+        assert_eq!(input.input, &b""[..]);
+        assert_eq!(message.answers.len(), 1);
+        assert_eq!(
+            message.authorities,
+            vec![ResourceRecord {
+                name: Name(vec![b"ns".to_vec(), b"example".to_vec(), b"com".to_vec()]),
+                resource_type: ResourceType::A,
+                resource_class: ResourceClass::Internet,
+                ttl: 3600,
+                rdata: ResourceData::A(Ipv4Addr::new(192, 0, 2, 1))
+            }]
+        );
+        assert_eq!(
+            message.additionals,
+            vec![ResourceRecord {
+                name: Name(vec![b"ns".to_vec(), b"example".to_vec(), b"com".to_vec()]),
+                resource_type: ResourceType::AAAA,
+                resource_class: ResourceClass::Internet,
+                ttl: 3600,
+                rdata: ResourceData::AAAA(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1))
+            }]
+        );
+    }
+
+    #[test]
+    fn test_message_response_edns() {
+        let message_bytes = hex::decode(
+            "656581800001000100000001076578616d706c6503636f6d0000010001c00c000100010000012c00045db8d82200002904d0000080000006fde90002abcd"
+        )
+        .unwrap();
+        let (input, message) = message(DnsFrameInput::new(&message_bytes)).unwrap();
+
+        assert_eq!(input.input, &b""[..]);
+        assert_eq!(message.additionals.len(), 1);
+        assert_eq!(
+            message.edns(),
+            Some(Edns {
+                udp_payload_size: 1232,
+                extended_rcode: 0,
+                version: 0,
+                dnssec_ok: true,
+                options: vec![EdnsOption::Unknown {
+                    code: 65001,
+                    data: vec![0xab, 0xcd],
+                }],
+            })
+        );
+    }
+
+    #[test]
+    fn test_decode_message_errors() {
+        let message_bytes = hex::decode("69068180").unwrap();
+        assert_eq!(
+            decode_message(&message_bytes),
+            Err(DecodeError {
+                kind: DecodeErrorKind::TruncatedHeader,
+                offset: 0,
+                section: Some(Section::Header),
+                index: None,
+            })
+        );
+
+        // the second answer is cut short
+        let message_bytes = hex::decode(
+            "690681800001000200000000076578616d706c6503636f6d0000010001c00c0001000100005a0200045db8d822c00c00010001"
+        )
+        .unwrap();
+        let error = decode_message(&message_bytes).unwrap_err();
+        assert_eq!(
+            error,
+            DecodeError {
+                kind: DecodeErrorKind::Truncated,
+                offset: 51,
+                section: Some(Section::Answer),
+                index: Some(1),
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "unexpected end of message at offset 51 in answer record 1"
+        );
+
+        let message_bytes = hex::decode(
+            "690681800001000100000000076578616d706c6503636f6d0000010001c00c0001000100005a0200045db8d8220000"
+        )
+        .unwrap();
+        assert_eq!(
+            decode_message(&message_bytes),
+            Err(DecodeError {
+                kind: DecodeErrorKind::TrailingBytes(2),
+                offset: 45,
+                section: None,
+                index: None,
+            })
+        );
+    }
+
+    #[test]
+    fn test_message_extended_rcode() {
+        // BADVERS: header rcode 0 with an extended rcode of 1 in the OPT record
+        let message_bytes = hex::decode(
+            "656581800001000000000001076578616d706c6503636f6d000001000100002904d0010000000000",
+        )
+        .unwrap();
+        let message = decode_message(&message_bytes).unwrap();
+        assert_eq!(message.header.flags.rcode, Rcode::NoError);
+        assert_eq!(message.rcode(), Rcode::BadVersion);
+        assert!(message
+            .to_string()
+            .starts_with(";; ->>HEADER<<- opcode: QUERY, rcode: BADVERS, id: 25957\n"));
+    }
 }
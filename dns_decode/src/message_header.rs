@@ -4,13 +4,18 @@ use nom::{
     bits::{bits, streaming::take},
     error::Error,
     number::complete::be_u16,
-    IResult,
+    Err, IResult,
 };
 use std::convert::Into;
 
-use crate::input::DnsFrameInput;
+use crate::{
+    error::{DecodeError, DecodeErrorKind},
+    input::DnsFrameInput,
+};
+
+const MESSAGE_HEADER_SIZE: usize = 12;
 
-fn dns_flags(input: DnsFrameInput) -> IResult<DnsFrameInput, Flags> {
+fn dns_flags(input: DnsFrameInput) -> IResult<DnsFrameInput, Flags, DecodeError> {
     fn dns_flags_inner(
         input: (DnsFrameInput, usize),
     ) -> IResult<(DnsFrameInput, usize), Flags, Error<(DnsFrameInput, usize)>> {
@@ -21,7 +26,9 @@ fn dns_flags(input: DnsFrameInput) -> IResult<DnsFrameInput, Flags> {
         let (input, truncated): BitParseResult = take(1usize)(input)?;
         let (input, recursion_desired): BitParseResult = take(1usize)(input)?;
         let (input, recursion_available): BitParseResult = take(1usize)(input)?;
-        let (input, _reserved): BitParseResult = take(3usize)(input)?;
+        let (input, _reserved): BitParseResult = take(1usize)(input)?;
+        let (input, authentic_data): BitParseResult = take(1usize)(input)?;
+        let (input, checking_disabled): BitParseResult = take(1usize)(input)?;
         let (input, rcode): BitParseResult = take(4usize)(input)?;
         Ok((
             input,
@@ -32,6 +39,8 @@ fn dns_flags(input: DnsFrameInput) -> IResult<DnsFrameInput, Flags> {
                 truncated: truncated.into(),
                 recursion_desired: recursion_desired.into(),
                 recursion_available: recursion_available.into(),
+                authentic_data: authentic_data.into(),
+                checking_disabled: checking_disabled.into(),
                 rcode: rcode.into(),
             },
         ))
@@ -39,7 +48,13 @@ fn dns_flags(input: DnsFrameInput) -> IResult<DnsFrameInput, Flags> {
     bits::<_, _, Error<(DnsFrameInput, usize)>, _, _>(dns_flags_inner)(input)
 }
 
-pub fn message_header(input: DnsFrameInput) -> IResult<DnsFrameInput, MessageHeader> {
+pub fn message_header(input: DnsFrameInput) -> IResult<DnsFrameInput, MessageHeader, DecodeError> {
+    if input.input.len() < MESSAGE_HEADER_SIZE {
+        return Err(Err::Error(DecodeError::new(
+            DecodeErrorKind::TruncatedHeader,
+            input,
+        )));
+    }
     let (input, message_id) = be_u16(input)?;
     let (input, flags) = dns_flags(input)?;
     let (input, query_count) = be_u16(input)?;
@@ -84,6 +99,8 @@ mod tests {
                         truncated: Truncated::NotTruncated,
                         recursion_desired: RecursionDesired::Desired,
                         recursion_available: RecursionAvailable::NotAvailable,
+                        authentic_data: AuthenticData::NotAuthentic,
+                        checking_disabled: CheckingDisabled::NotDisabled,
                         rcode: Rcode::NoError,
                     },
                     query_count: 1,
@@ -116,6 +133,8 @@ mod tests {
                         truncated: Truncated::NotTruncated,
                         recursion_desired: RecursionDesired::Desired,
                         recursion_available: RecursionAvailable::Available,
+                        authentic_data: AuthenticData::NotAuthentic,
+                        checking_disabled: CheckingDisabled::NotDisabled,
                         rcode: Rcode::NoError,
                     },
                     query_count: 1,
@@ -126,4 +145,54 @@ mod tests {
             ))
         );
     }
+
+    #[test]
+    fn message_header_all_flags() {
+        let dns_message_bytes = hex::decode("abcd97b50001000000000000").unwrap();
+
+        let (_, message_header) = message_header(DnsFrameInput::new(&dns_message_bytes)).unwrap();
+        assert_eq!(
+            message_header.flags,
+            Flags {
+                qr: QR::Response,
+                opcode: Opcode::Status,
+                aa: AuthoritativeAnswer::Authoritative,
+                truncated: Truncated::Truncated,
+                recursion_desired: RecursionDesired::Desired,
+                recursion_available: RecursionAvailable::Available,
+                authentic_data: AuthenticData::Authentic,
+                checking_disabled: CheckingDisabled::Disabled,
+                rcode: Rcode::Refused,
+            }
+        );
+    }
+
+    #[test]
+    fn message_header_truncated() {
+        let dns_message_bytes = hex::decode("6906818000010001").unwrap();
+
+        let result = message_header(DnsFrameInput::new(&dns_message_bytes));
+        assert_eq!(
+            result,
+            Err(Err::Error(DecodeError {
+                kind: DecodeErrorKind::TruncatedHeader,
+                offset: 0,
+                section: None,
+                index: None,
+            }))
+        );
+    }
+
+    #[test]
+    fn message_header_opcode_and_rcode_registries() {
+        let dns_message_bytes = hex::decode("abcda4090001000000000000").unwrap();
+        let (_, header) = message_header(DnsFrameInput::new(&dns_message_bytes)).unwrap();
+        assert_eq!(header.flags.opcode, Opcode::Notify);
+        assert_eq!(header.flags.rcode, Rcode::NotAuth);
+
+        let dns_message_bytes = hex::decode("abcdf80f0001000000000000").unwrap();
+        let (_, header) = message_header(DnsFrameInput::new(&dns_message_bytes)).unwrap();
+        assert_eq!(header.flags.opcode, Opcode::Unknown(15));
+        assert_eq!(header.flags.rcode, Rcode::Unknown(15));
+    }
 }
@@ -1,23 +1,54 @@
-use crate::input::DnsFrameInput;
+use crate::{
+    error::{DecodeError, DecodeErrorKind},
+    input::DnsFrameInput,
+};
+use dns_types::MAX_NAME_LENGTH;
 use nom::{
-    bytes::complete::{tag, take},
-    combinator::iterator,
-    error::{Error, ErrorKind},
+    bytes::complete::take,
     number::complete::{be_u16, be_u8},
     Err, IResult,
 };
 
-pub fn name(input: DnsFrameInput) -> IResult<DnsFrameInput, Vec<String>> {
-    let mut it = iterator(input, name_label);
-    let labels: Vec<String> = it.collect();
-    let (input, ()) = it.finish()?;
-    let (_, str_length) = be_u8(input)?;
-    if is_pointer(str_length) {
-        let (input, pointer_labels) = name_pointer(input)?;
-        return Ok((input, [labels, pointer_labels].concat()));
+/// Limit on the compression pointers followed for a single name
+const MAX_POINTER_HOPS: usize = 16;
+
+pub fn name(input: DnsFrameInput) -> IResult<DnsFrameInput, Vec<Vec<u8>>, DecodeError> {
+    let mut labels: Vec<Vec<u8>> = vec![];
+    let mut name_length = 1;
+    let mut pointer_hops = 0;
+    // once a pointer is followed, decoding resumes after the first pointer
+    let mut remaining: Option<DnsFrameInput> = None;
+    let mut input = input;
+    loop {
+        let (_, str_length) = be_u8(input)?;
+        if str_length == 0 {
+            let (input, _) = be_u8(input)?;
+            return Ok((remaining.unwrap_or(input), labels));
+        }
+        if is_pointer(str_length) {
+            pointer_hops += 1;
+            if pointer_hops > MAX_POINTER_HOPS {
+                return Err(Err::Error(DecodeError::new(
+                    DecodeErrorKind::PointerLoop,
+                    input,
+                )));
+            }
+            let (after_pointer, pointed) = name_pointer(input)?;
+            remaining.get_or_insert(after_pointer);
+            input = pointed;
+            continue;
+        }
+        let (after_label, label) = name_label(input)?;
+        name_length += label.len() + 1;
+        if name_length > MAX_NAME_LENGTH {
+            return Err(Err::Error(DecodeError::new(
+                DecodeErrorKind::NameTooLong,
+                input,
+            )));
+        }
+        labels.push(label);
+        input = after_label;
     }
-    let (input, _) = tag(b"\x00")(input)?;
-    Ok((input, labels))
 }
 
 fn is_pointer(b: u8) -> bool {
@@ -32,30 +63,50 @@ fn label_size(b: u8) -> Option<u8> {
     }
 }
 
-fn name_label(input: DnsFrameInput) -> IResult<DnsFrameInput, String> {
-    let (input, str_length) = be_u8(input)?;
+fn name_label(input: DnsFrameInput) -> IResult<DnsFrameInput, Vec<u8>, DecodeError> {
+    let (label_input, str_length) = be_u8(input)?;
     match label_size(str_length) {
-        None => Err(Err::Error(Error::new(input, ErrorKind::Eof))),
+        None => Err(Err::Error(DecodeError::new(
+            DecodeErrorKind::BadLabelLength(str_length),
+            input,
+        ))),
         Some(size) => {
-            let (input, str) = take(size)(input)?;
-            Ok((input, String::from_utf8(str.input.to_vec()).unwrap()))
+            let (label_input, str) = take(size)(label_input)?;
+            Ok((label_input, str.input.to_vec()))
         }
     }
 }
 
-fn name_pointer(input: DnsFrameInput) -> IResult<DnsFrameInput, Vec<String>> {
-    let (input, pointer) = be_u16(input)?;
+/// Returns the input following the pointer and the input the pointer points to, which must
+/// be strictly before the pointer so that pointers can not loop.
+fn name_pointer(input: DnsFrameInput) -> IResult<DnsFrameInput, DnsFrameInput, DecodeError> {
+    let (remaining, pointer) = be_u16(input)?;
     let pointer = (pointer & 0b0011_1111_1111_1111) as usize;
-    let (_, labels) = name(DnsFrameInput {
-        frame: input.frame,
-        input: &input.frame[pointer..],
-    })?;
-    Ok((input, labels))
+    let kind = if pointer >= input.frame.len() {
+        Some(DecodeErrorKind::PointerOutOfRange(pointer))
+    } else if pointer == input.position() {
+        Some(DecodeErrorKind::PointerLoop)
+    } else if pointer > input.position() {
+        Some(DecodeErrorKind::ForwardPointer(pointer))
+    } else {
+        None
+    };
+    if let Some(kind) = kind {
+        return Err(Err::Error(DecodeError::new(kind, input)));
+    }
+    Ok((
+        remaining,
+        DnsFrameInput {
+            frame: input.frame,
+            input: &input.frame[pointer..],
+        },
+    ))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use dns_types::Name;
 
     #[test]
     fn test_name_label() {
@@ -69,11 +120,7 @@ mod tests {
                     frame: &dns_query_bytes,
                     input: &b""[..],
                 },
-                vec![
-                    String::from("www"),
-                    String::from("mydomain"),
-                    String::from("com")
-                ]
+                vec![b"www".to_vec(), b"mydomain".to_vec(), b"com".to_vec()]
             ))
         );
     }
@@ -93,7 +140,7 @@ mod tests {
                     frame: &message,
                     input: &message[31..],
                 },
-                vec![String::from("example"), String::from("com")]
+                vec![b"example".to_vec(), b"com".to_vec()]
             ))
         );
     }
@@ -110,7 +157,7 @@ mod tests {
                     frame: &dns_query_bytes,
                     input: &dns_query_bytes[4..],
                 },
-                String::from("www"),
+                b"www".to_vec(),
             ))
         );
 
@@ -122,7 +169,7 @@ mod tests {
                     frame: &dns_query_bytes[4..],
                     input: &dns_query_bytes[13..],
                 },
-                String::from("mydomain"),
+                b"mydomain".to_vec(),
             ))
         );
 
@@ -134,8 +181,124 @@ mod tests {
                     frame: &dns_query_bytes[13..],
                     input: &[0u8][..]
                 },
-                String::from("com"),
+                b"com".to_vec(),
             ))
         );
     }
+
+    #[test]
+    fn test_name_bad_label_length() {
+        let dns_query_bytes = hex::decode("03777777406D79646F6D61696E03636F6D00").unwrap();
+        let result = name(DnsFrameInput::new(&dns_query_bytes));
+
+        assert_eq!(
+            result,
+            Err(Err::Error(DecodeError {
+                kind: DecodeErrorKind::BadLabelLength(0x40),
+                offset: 4,
+                section: None,
+                index: None,
+            }))
+        );
+    }
+
+    #[test]
+    fn test_name_binary_label() {
+        let dns_query_bytes = hex::decode("0377777703C3282E036F726700").unwrap();
+        let (_, labels) = name(DnsFrameInput::new(&dns_query_bytes)).unwrap();
+
+        assert_eq!(
+            labels,
+            vec![b"www".to_vec(), vec![0xC3, 0x28, b'.'], b"org".to_vec()]
+        );
+        assert_eq!(Name(labels).to_string(), r"www.\195\(\..org.");
+    }
+
+    #[test]
+    fn test_name_pointer_out_of_range() {
+        let dns_query_bytes = hex::decode("03777777C0FF").unwrap();
+        let result = name(DnsFrameInput::new(&dns_query_bytes));
+
+        assert_eq!(
+            result,
+            Err(Err::Error(DecodeError {
+                kind: DecodeErrorKind::PointerOutOfRange(0xFF),
+                offset: 4,
+                section: None,
+                index: None,
+            }))
+        );
+    }
+
+    #[test]
+    fn test_name_pointer_loop() {
+        // pointer to itself
+        let message = hex::decode("03777777C004").unwrap();
+        let result = name(DnsFrameInput::new(&message));
+        assert_eq!(
+            result,
+            Err(Err::Error(DecodeError {
+                kind: DecodeErrorKind::PointerLoop,
+                offset: 4,
+                section: None,
+                index: None,
+            }))
+        );
+
+        // chain of pointers each pointing at the previous one
+        let mut message = vec![0u8];
+        for i in 0..20u16 {
+            let pointer = 0xC000 | if i == 0 { 0 } else { 1 + (i - 1) * 2 };
+            message.extend_from_slice(&pointer.to_be_bytes());
+        }
+        let start = message.len() - 2;
+        let result = name(DnsFrameInput {
+            frame: &message,
+            input: &message[start..],
+        });
+        assert_eq!(
+            result,
+            Err(Err::Error(DecodeError {
+                kind: DecodeErrorKind::PointerLoop,
+                offset: start - 2 * 16,
+                section: None,
+                index: None,
+            }))
+        );
+    }
+
+    #[test]
+    fn test_name_forward_pointer() {
+        let message = hex::decode("03777777C0060377777700").unwrap();
+        let result = name(DnsFrameInput::new(&message));
+        assert_eq!(
+            result,
+            Err(Err::Error(DecodeError {
+                kind: DecodeErrorKind::ForwardPointer(6),
+                offset: 4,
+                section: None,
+                index: None,
+            }))
+        );
+    }
+
+    #[test]
+    fn test_name_too_long() {
+        let label = format!("3F{}", "61".repeat(63));
+        let message = hex::decode(format!("{}00", label.repeat(4))).unwrap();
+        let result = name(DnsFrameInput::new(&message));
+        assert_eq!(
+            result,
+            Err(Err::Error(DecodeError {
+                kind: DecodeErrorKind::NameTooLong,
+                offset: 192,
+                section: None,
+                index: None,
+            }))
+        );
+
+        let message = hex::decode(format!("{}3D{}00", label.repeat(3), "61".repeat(61))).unwrap();
+        let (_, labels) = name(DnsFrameInput::new(&message)).unwrap();
+        assert_eq!(labels.len(), 4);
+    }
 }
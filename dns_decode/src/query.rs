@@ -3,9 +3,9 @@ use dns_types::{query::*, Name};
 use nom::{number::complete::be_u16, IResult};
 use std::convert::Into;
 
-use crate::{input::DnsFrameInput, name::name};
+use crate::{error::DecodeError, input::DnsFrameInput, name::name};
 
-pub fn query(input: DnsFrameInput) -> IResult<DnsFrameInput, Query> {
+pub fn query(input: DnsFrameInput) -> IResult<DnsFrameInput, Query, DecodeError> {
     let (input, name) = name(input)?;
     let (input, query_type) = be_u16(input)?;
     let (input, query_class) = be_u16(input)?;
@@ -36,7 +36,7 @@ mod tests {
                     input: &b""[..],
                 },
                 Query {
-                    name: Name(vec![String::from("example"), String::from("com")]),
+                    name: Name(vec![b"example".to_vec(), b"com".to_vec()]),
                     query_type: QueryType::A,
                     query_class: QueryClass::Internet,
                 }
@@ -1,7 +1,10 @@
 use dns_types::{resource_record::*, Name};
 use nom::{
-    multi::length_value,
-    number::complete::{be_u128, be_u16, be_u32},
+    bytes::complete::take,
+    combinator::{all_consuming, map, rest},
+    error::ParseError,
+    multi::{length_data, many0},
+    number::complete::{be_u128, be_u16, be_u32, be_u8},
     IResult, InputIter, InputLength, Slice,
 };
 use std::ops::RangeFrom;
@@ -10,44 +13,133 @@ use std::{
     net::{Ipv4Addr, Ipv6Addr},
 };
 
-use crate::{input::DnsFrameInput, name::name};
+use crate::{
+    edns::edns_options,
+    error::{DecodeError, DecodeErrorKind},
+    input::DnsFrameInput,
+    name::name,
+};
 
-fn a_record<Input>(input: Input) -> IResult<Input, ResourceData>
+fn a_record<Input, E>(input: Input) -> IResult<Input, ResourceData, E>
 where
     Input: Slice<RangeFrom<usize>> + InputIter<Item = u8> + InputLength,
+    E: ParseError<Input>,
 {
     let (input, ip_address) = be_u32(input)?;
     Ok((input, ResourceData::A(Ipv4Addr::from(ip_address))))
 }
 
-fn aaaa_record<Input>(input: Input) -> IResult<Input, ResourceData>
+fn aaaa_record<Input, E>(input: Input) -> IResult<Input, ResourceData, E>
 where
     Input: Slice<RangeFrom<usize>> + InputIter<Item = u8> + InputLength,
+    E: ParseError<Input>,
 {
     let (input, ip_address) = be_u128(input)?;
     Ok((input, ResourceData::AAAA(Ipv6Addr::from(ip_address))))
 }
 
-fn resource_data<Input>(
+fn soa_record(input: DnsFrameInput) -> IResult<DnsFrameInput, ResourceData, DecodeError> {
+    let (input, mname) = name(input)?;
+    let (input, rname) = name(input)?;
+    let (input, serial) = be_u32(input)?;
+    let (input, refresh) = be_u32(input)?;
+    let (input, retry) = be_u32(input)?;
+    let (input, expire) = be_u32(input)?;
+    let (input, minimum) = be_u32(input)?;
+    Ok((
+        input,
+        ResourceData::SOA {
+            mname: Name(mname),
+            rname: Name(rname),
+            serial,
+            refresh,
+            retry,
+            expire,
+            minimum,
+        },
+    ))
+}
+
+fn mx_record(input: DnsFrameInput) -> IResult<DnsFrameInput, ResourceData, DecodeError> {
+    let (input, preference) = be_u16(input)?;
+    let (input, exchange) = name(input)?;
+    Ok((
+        input,
+        ResourceData::MX {
+            preference,
+            exchange: Name(exchange),
+        },
+    ))
+}
+
+fn srv_record(input: DnsFrameInput) -> IResult<DnsFrameInput, ResourceData, DecodeError> {
+    let (input, priority) = be_u16(input)?;
+    let (input, weight) = be_u16(input)?;
+    let (input, port) = be_u16(input)?;
+    let (input, target) = name(input)?;
+    Ok((
+        input,
+        ResourceData::SRV {
+            priority,
+            weight,
+            port,
+            target: Name(target),
+        },
+    ))
+}
+
+fn character_strings(input: DnsFrameInput) -> IResult<DnsFrameInput, Vec<Vec<u8>>, DecodeError> {
+    many0(map(length_data(be_u8), |string: DnsFrameInput| {
+        string.input.to_vec()
+    }))(input)
+}
+
+fn resource_data(
     resource_type: ResourceType,
-) -> impl Fn(Input) -> IResult<Input, ResourceData>
-where
-    Input: Slice<RangeFrom<usize>> + InputIter<Item = u8> + InputLength,
-{
-    move |input: Input| match resource_type {
+) -> impl Fn(DnsFrameInput) -> IResult<DnsFrameInput, ResourceData, DecodeError> {
+    move |input: DnsFrameInput| match resource_type {
         ResourceType::A => a_record(input),
         ResourceType::AAAA => aaaa_record(input),
-        _ => todo!(),
+        ResourceType::NS => map(name, |labels| ResourceData::NS(Name(labels)))(input),
+        ResourceType::CNAME => map(name, |labels| ResourceData::CNAME(Name(labels)))(input),
+        ResourceType::PTR => map(name, |labels| ResourceData::PTR(Name(labels)))(input),
+        ResourceType::DNAME => map(name, |labels| ResourceData::DNAME(Name(labels)))(input),
+        ResourceType::SOA => soa_record(input),
+        ResourceType::MX => mx_record(input),
+        ResourceType::SRV => srv_record(input),
+        ResourceType::TXT => map(character_strings, ResourceData::TXT)(input),
+        ResourceType::SPF => map(character_strings, ResourceData::SPF)(input),
+        ResourceType::OPT => map(edns_options, ResourceData::OPT)(input),
+        ResourceType::WKS | ResourceType::Unknown(_) => {
+            map(rest, |data: DnsFrameInput| ResourceData::Unknown {
+                rtype: resource_type.into(),
+                data: data.input.to_vec(),
+            })(input)
+        }
     }
 }
 
-pub fn resource_record(input: DnsFrameInput) -> IResult<DnsFrameInput, ResourceRecord> {
+pub fn resource_record(
+    input: DnsFrameInput,
+) -> IResult<DnsFrameInput, ResourceRecord, DecodeError> {
     let (input, name) = name(input)?;
     let (input, resource_type) = be_u16(input)?;
     let (input, resource_class) = be_u16(input)?;
     let (input, ttl) = be_u32(input)?;
     let resource_type: ResourceType = resource_type.into();
-    let (input, rdata) = length_value(be_u16, resource_data(resource_type))(input)?;
+    let (input, rdlength) = be_u16(input)?;
+    let (input, rdata_input) = take(rdlength)(input)?;
+    // running out of rdata, or leaving some of it unread, means RDLENGTH disagrees with the rdata,
+    // unless the error comes from a compression pointer leading elsewhere in the message
+    let rdata_range = rdata_input.position()..=rdata_input.position() + rdata_input.input.len();
+    let (_, rdata) = all_consuming(resource_data(resource_type))(rdata_input).map_err(|e| {
+        e.map(|e| match e.kind {
+            DecodeErrorKind::Truncated if rdata_range.contains(&e.offset) => {
+                DecodeError::new(DecodeErrorKind::RdataLengthMismatch(rdlength), rdata_input)
+            }
+            _ => e,
+        })
+    })?;
     Ok((
         input,
         ResourceRecord {
@@ -63,6 +155,7 @@ pub fn resource_record(input: DnsFrameInput) -> IResult<DnsFrameInput, ResourceR
 #[cfg(test)]
 mod tests {
     use super::*;
+    use nom::Err;
 
     #[test]
     fn test_resource_record_a_record() {
@@ -80,7 +173,7 @@ mod tests {
                     input: &b""[..],
                 },
                 ResourceRecord {
-                    name: Name(vec![String::from("example"), String::from("com")]),
+                    name: Name(vec![b"example".to_vec(), b"com".to_vec()]),
                     resource_type: ResourceType::A,
                     resource_class: ResourceClass::Internet,
                     ttl: 23042,
@@ -107,7 +200,7 @@ mod tests {
                     input: &b""[..],
                 },
                 ResourceRecord {
-                    name: Name(vec![String::from("example"), String::from("com")]),
+                    name: Name(vec![b"example".to_vec(), b"com".to_vec()]),
                     resource_type: ResourceType::AAAA,
                     resource_class: ResourceClass::Internet,
                     ttl: 78024,
@@ -118,4 +211,227 @@ mod tests {
             ))
         );
     }
+
+    #[test]
+    fn test_resource_record_ptr_record() {
+        let message = hex::decode("123481800001000100000000013101310131013107696e2d61646472046172706100000c0001c00c000c000100000e100011036f6e65036f6e65036f6e65036f6e6500").unwrap();
+        let result = resource_record(DnsFrameInput {
+            frame: &message,
+            input: &message[38..],
+        });
+
+        assert_eq!(
+            result,
+            Ok((
+                DnsFrameInput {
+                    frame: &message,
+                    input: &b""[..],
+                },
+                ResourceRecord {
+                    name: Name::from_ip(Ipv4Addr::new(1, 1, 1, 1).into()),
+                    resource_type: ResourceType::PTR,
+                    resource_class: ResourceClass::Internet,
+                    ttl: 3600,
+                    rdata: ResourceData::PTR(Name(vec![
+                        b"one".to_vec(),
+                        b"one".to_vec(),
+                        b"one".to_vec(),
+                        b"one".to_vec()
+                    ]))
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn test_resource_record_cname_record() {
+        let message = hex::decode("beef81800001000200000000037777770667697468756203636f6d0000010001c00c0005000100000e100002c010c010000100010000003c00048c527904").unwrap();
+        let result = resource_record(DnsFrameInput {
+            frame: &message,
+            input: &message[32..],
+        });
+
+        assert_eq!(
+            result,
+            Ok((
+                DnsFrameInput {
+                    frame: &message,
+                    input: &message[46..],
+                },
+                ResourceRecord {
+                    name: Name(vec![b"www".to_vec(), b"github".to_vec(), b"com".to_vec()]),
+                    resource_type: ResourceType::CNAME,
+                    resource_class: ResourceClass::Internet,
+                    ttl: 3600,
+                    rdata: ResourceData::CNAME(Name(vec![b"github".to_vec(), b"com".to_vec()]))
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn test_resource_record_soa_record() {
+        let message = hex::decode("424281830001000000010000046e6f7065076578616d706c6503636f6d0000010001c0110006000100000e10002c026e73056963616e6e036f726700036e6f6303646e73c03178a3f17500001c2000000e100012750000000e10").unwrap();
+        let (input, resource_record) = resource_record(DnsFrameInput {
+            frame: &message,
+            input: &message[34..],
+        })
+        .unwrap();
+
+        assert_eq!(input.input, &b""[..]);
+        assert_eq!(
+            resource_record.rdata,
+            ResourceData::SOA {
+                mname: Name(vec![b"ns".to_vec(), b"icann".to_vec(), b"org".to_vec()]),
+                rname: Name(vec![
+                    b"noc".to_vec(),
+                    b"dns".to_vec(),
+                    b"icann".to_vec(),
+                    b"org".to_vec()
+                ]),
+                serial: 2024010101,
+                refresh: 7200,
+                retry: 3600,
+                expire: 1209600,
+                minimum: 3600,
+            }
+        );
+        assert_eq!(
+            resource_record.rdata.to_string(),
+            "ns.icann.org. noc.dns.icann.org. 2024010101 7200 3600 1209600 3600"
+        );
+    }
+
+    #[test]
+    fn test_resource_record_mx_record() {
+        let message = hex::decode("515181800001000100000000076578616d706c6503636f6d00000f0001c00c000f00010000012c0009000a046d61696cc00c").unwrap();
+        let (input, resource_record) = resource_record(DnsFrameInput {
+            frame: &message,
+            input: &message[29..],
+        })
+        .unwrap();
+
+        assert_eq!(input.input, &b""[..]);
+        assert_eq!(
+            resource_record.rdata,
+            ResourceData::MX {
+                preference: 10,
+                exchange: Name(vec![b"mail".to_vec(), b"example".to_vec(), b"com".to_vec()]),
+            }
+        );
+        assert_eq!(resource_record.rdata.to_string(), "10 mail.example.com.");
+    }
+
+    #[test]
+    fn test_resource_record_srv_record() {
+        let message = hex::decode("525281800001000100000000045f736970045f746370076578616d706c6503636f6d0000210001c00c002100010000012c0017000a003c13c403736970076578616d706c6503636f6d00").unwrap();
+        let (input, resource_record) = resource_record(DnsFrameInput {
+            frame: &message,
+            input: &message[39..],
+        })
+        .unwrap();
+
+        assert_eq!(input.input, &b""[..]);
+        assert_eq!(
+            resource_record.rdata,
+            ResourceData::SRV {
+                priority: 10,
+                weight: 60,
+                port: 5060,
+                target: Name(vec![b"sip".to_vec(), b"example".to_vec(), b"com".to_vec()]),
+            }
+        );
+        assert_eq!(
+            resource_record.rdata.to_string(),
+            "10 60 5060 sip.example.com."
+        );
+    }
+
+    #[test]
+    fn test_resource_record_txt_record() {
+        let message = hex::decode("535381800001000100000000076578616d706c6503636f6d0000100001c00c001000010000012c001e0b763d73706631202d616c6c0c7361792022686922205c6f2f0461000aff").unwrap();
+        let (input, resource_record) = resource_record(DnsFrameInput {
+            frame: &message,
+            input: &message[29..],
+        })
+        .unwrap();
+
+        assert_eq!(input.input, &b""[..]);
+        assert_eq!(
+            resource_record.rdata,
+            ResourceData::TXT(vec![
+                b"v=spf1 -all".to_vec(),
+                b"say \"hi\" \\o/".to_vec(),
+                vec![b'a', 0x00, b'\n', 0xff],
+            ])
+        );
+        assert_eq!(
+            resource_record.rdata.to_string(),
+            r#""v=spf1 -all" "say \"hi\" \\o/" "a\000\010\255""#
+        );
+    }
+
+    #[test]
+    fn test_resource_record_unknown_record() {
+        let message = hex::decode("545481800001000100000000076578616d706c6503636f6d00fffe0001c00cfffe00010000012c00040a000001").unwrap();
+        let (input, resource_record) = resource_record(DnsFrameInput {
+            frame: &message,
+            input: &message[29..],
+        })
+        .unwrap();
+
+        assert_eq!(input.input, &b""[..]);
+        assert_eq!(resource_record.resource_type, ResourceType::Unknown(65534));
+        assert_eq!(
+            resource_record.rdata,
+            ResourceData::Unknown {
+                rtype: 65534,
+                data: vec![0x0a, 0x00, 0x00, 0x01],
+            }
+        );
+        assert_eq!(
+            resource_record.to_string(),
+            "example.com.\t\t300\tIN\tTYPE65534\t\\# 4 0a000001\n"
+        );
+    }
+
+    #[test]
+    fn test_resource_record_rdata_length_mismatch() {
+        // A record with an RDLENGTH of 3
+        let message = hex::decode("690681800001000100000000076578616d706c6503636f6d0000010001c00c0001000100005a0200035db8d8").unwrap();
+        let result = resource_record(DnsFrameInput {
+            frame: &message,
+            input: &message[29..],
+        });
+
+        assert_eq!(
+            result,
+            Err(Err::Error(DecodeError {
+                kind: DecodeErrorKind::RdataLengthMismatch(3),
+                offset: 41,
+                section: None,
+                index: None,
+            }))
+        );
+    }
+
+    #[test]
+    fn test_resource_record_truncated_behind_pointer() {
+        // CNAME whose rdata points back at the TTL, read as a label running past the message
+        let message = hex::decode("690681800001000100000000076578616d706c6503636f6d0000010001c00c0005000100000e100002c026").unwrap();
+        let result = resource_record(DnsFrameInput {
+            frame: &message,
+            input: &message[29..],
+        });
+
+        assert_eq!(
+            result,
+            Err(Err::Error(DecodeError {
+                kind: DecodeErrorKind::Truncated,
+                offset: 39,
+                section: None,
+                index: None,
+            }))
+        );
+    }
 }
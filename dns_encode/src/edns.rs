@@ -0,0 +1,121 @@
+use dns_types::edns::*;
+use std::{
+    io::{Error, ErrorKind, Write},
+    net::IpAddr,
+};
+
+fn encode_option_data(option: &EdnsOption, buffer: &mut Vec<u8>) -> Result<(), Error> {
+    match option {
+        EdnsOption::ClientSubnet(client_subnet) => {
+            buffer.write_all(&client_subnet.family().to_be_bytes())?;
+            buffer.write_all(&[
+                client_subnet.source_prefix_length,
+                client_subnet.scope_prefix_length,
+            ])?;
+            // only the octets covered by the source prefix are sent
+            let address_len = (client_subnet.source_prefix_length as usize).div_ceil(8);
+            let address = match client_subnet.address {
+                IpAddr::V4(ip) => ip.octets().to_vec(),
+                IpAddr::V6(ip) => ip.octets().to_vec(),
+            };
+            let mut address = address
+                .get(..address_len)
+                .ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::InvalidData,
+                        "client subnet source prefix is longer than the address",
+                    )
+                })?
+                .to_vec();
+            // the address bits past the source prefix must be zero (RFC 7871 6)
+            let partial_bits = client_subnet.source_prefix_length % 8;
+            if partial_bits != 0 {
+                address[address_len - 1] &= 0xff << (8 - partial_bits);
+            }
+            buffer.write_all(&address)
+        }
+        EdnsOption::Cookie(cookie) => {
+            buffer.write_all(&cookie.client)?;
+            if let Some(server) = &cookie.server {
+                buffer.write_all(server)?;
+            }
+            Ok(())
+        }
+        EdnsOption::Nsid(nsid) => buffer.write_all(&nsid.0),
+        EdnsOption::Padding(padding) => buffer.write_all(&vec![0u8; padding.length as usize]),
+        EdnsOption::ExtendedError(extended_error) => {
+            buffer.write_all(&extended_error.info_code.to_be_bytes())?;
+            buffer.write_all(extended_error.extra_text.as_bytes())
+        }
+        EdnsOption::Unknown { data, .. } => buffer.write_all(data),
+    }
+}
+
+pub fn encode_edns_options<W: Write>(options: &[EdnsOption], writer: &mut W) -> Result<(), Error> {
+    let mut data: Vec<u8> = Vec::new();
+    for option in options {
+        data.clear();
+        encode_option_data(option, &mut data)?;
+        let option_len = u16::try_from(data.len())
+            .map_err(|_| Error::new(ErrorKind::InvalidData, "EDNS option exceeds 65535 bytes"))?;
+        writer.write_all(&option.code().to_be_bytes())?;
+        writer.write_all(&option_len.to_be_bytes())?;
+        writer.write_all(&data)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    #[test]
+    fn test_encode_edns_options() {
+        let options_bytes = hex::decode(concat!(
+            "fde90002abcd",
+            "00030000",
+            "0008000b0002380020010db8000000",
+            "000a00080102030405060708",
+            "000c0003000000",
+            "000f00020012",
+        ))
+        .unwrap();
+        let options = vec![
+            EdnsOption::Unknown {
+                code: 65001,
+                data: vec![0xab, 0xcd],
+            },
+            EdnsOption::Nsid(Nsid(vec![])),
+            EdnsOption::ClientSubnet(ClientSubnet {
+                source_prefix_length: 56,
+                scope_prefix_length: 0,
+                address: IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 0)),
+            }),
+            EdnsOption::Cookie(Cookie {
+                client: [1, 2, 3, 4, 5, 6, 7, 8],
+                server: None,
+            }),
+            EdnsOption::Padding(Padding { length: 3 }),
+            EdnsOption::ExtendedError(ExtendedError {
+                info_code: 18,
+                extra_text: String::new(),
+            }),
+        ];
+        let mut buffer: Vec<u8> = Vec::with_capacity(55);
+        encode_edns_options(&options, &mut buffer).unwrap();
+        assert_eq!(buffer, options_bytes);
+    }
+
+    #[test]
+    fn test_encode_client_subnet_masks_address() {
+        let options = vec![EdnsOption::ClientSubnet(ClientSubnet {
+            source_prefix_length: 20,
+            scope_prefix_length: 0,
+            address: IpAddr::V4(Ipv4Addr::new(198, 51, 100, 7)),
+        })];
+        let mut buffer: Vec<u8> = Vec::new();
+        encode_edns_options(&options, &mut buffer).unwrap();
+        assert_eq!(buffer, hex::decode("0008000700011400c63360").unwrap());
+    }
+}
@@ -1,4 +1,7 @@
+pub mod edns;
 pub mod message;
 pub mod message_header;
+pub mod name;
 pub mod query;
-pub use message::encode_message;
+pub mod resource_record;
+pub use message::{encode_message, encode_message_with_options, EncodeOptions};
@@ -1,20 +1,59 @@
 use crate::message_header::encode_message_header;
+use crate::name::NameCompression;
 use crate::query::encode_query;
+use crate::resource_record::encode_resource_record;
 use dns_types::*;
 use std::io::{Error, Write};
 
+#[derive(Debug, Clone, Copy)]
+pub struct EncodeOptions {
+    /// Replace repeated name suffixes with pointers. Disable for canonical (DNSSEC) output.
+    pub compress_names: bool,
+}
+
+impl Default for EncodeOptions {
+    fn default() -> Self {
+        Self {
+            compress_names: true,
+        }
+    }
+}
+
 pub fn encode_message<W: Write>(message: &Message, writer: &mut W) -> Result<(), Error> {
-    encode_message_header(&message.header, writer)?;
+    encode_message_with_options(message, writer, EncodeOptions::default())
+}
+
+pub fn encode_message_with_options<W: Write>(
+    message: &Message,
+    writer: &mut W,
+    options: EncodeOptions,
+) -> Result<(), Error> {
+    let mut compression = if options.compress_names {
+        NameCompression::new()
+    } else {
+        NameCompression::disabled()
+    };
+    let mut buffer: Vec<u8> = Vec::with_capacity(512);
+    encode_message_header(&message.header, &mut buffer)?;
     for query in message.queries.as_slice() {
-        encode_query(query, writer)?;
+        encode_query(query, &mut buffer, &mut compression)?;
+    }
+    let records = message
+        .answers
+        .iter()
+        .chain(message.authorities.iter())
+        .chain(message.additionals.iter());
+    for record in records {
+        encode_resource_record(record, &mut buffer, &mut compression)?;
     }
-    // TODO answers
+    writer.write_all(&buffer)?;
     Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use dns_decode::decode_message;
 
     #[test]
     fn test_encode_message() {
@@ -30,6 +69,8 @@ mod tests {
                     truncated: Truncated::NotTruncated,
                     recursion_desired: RecursionDesired::Desired,
                     recursion_available: RecursionAvailable::NotAvailable,
+                    authentic_data: AuthenticData::NotAuthentic,
+                    checking_disabled: CheckingDisabled::NotDisabled,
                     rcode: Rcode::NoError,
                 },
                 query_count: 1,
@@ -38,14 +79,104 @@ mod tests {
                 additional_count: 0,
             },
             queries: vec![Query {
-                name: Name(vec![String::from("example"), String::from("com")]),
+                name: Name(vec![b"example".to_vec(), b"com".to_vec()]),
                 query_type: QueryType::A,
                 query_class: QueryClass::Internet,
             }],
             answers: vec![],
+            authorities: vec![],
+            additionals: vec![],
         };
         let mut buffer: Vec<u8> = Vec::with_capacity(50);
         encode_message(&message, &mut buffer).unwrap();
         assert_eq!(buffer, message_bytes);
     }
+
+    fn assert_round_trip(message_hex: &str) {
+        let message_bytes = hex::decode(message_hex).unwrap();
+        let message = decode_message(&message_bytes).unwrap();
+        let mut buffer: Vec<u8> = Vec::with_capacity(message_bytes.len());
+        encode_message(&message, &mut buffer).unwrap();
+        assert_eq!(decode_message(&buffer).unwrap(), message);
+
+        let mut buffer: Vec<u8> = Vec::with_capacity(message_bytes.len());
+        let options = EncodeOptions {
+            compress_names: false,
+        };
+        encode_message_with_options(&message, &mut buffer, options).unwrap();
+        assert_eq!(decode_message(&buffer).unwrap(), message);
+    }
+
+    #[test]
+    fn test_encode_message_round_trip() {
+        // A answer
+        assert_round_trip("690681800001000100000000076578616d706c6503636f6d0000010001c00c0001000100005a0200045db8d822");
+        // AAAA answer
+        assert_round_trip("653081800001000100000000076578616d706c6503636f6d00001c0001c00c001c0001000130c8001026062800022000010248189325c81946");
+        // answer, authority and additional sections
+        assert_round_trip("123481800001000100010001076578616d706c6503636f6d0000010001c00c0001000100000e1000045db8d822026e73c00c0001000100000e100004c0000201c02d001c000100000e10001020010db8000000000000000000000001");
+        // CNAME chain with a pointer inside the rdata
+        assert_round_trip("beef81800001000200000000037777770667697468756203636f6d0000010001c00c0005000100000e100002c010c010000100010000003c00048c527904");
+        // SOA in the authority section of an NXDOMAIN
+        assert_round_trip("424281830001000000010000046e6f7065076578616d706c6503636f6d0000010001c0110006000100000e10002c026e73056963616e6e036f726700036e6f6303646e73c03178a3f17500001c2000000e100012750000000e10");
+        // MX
+        assert_round_trip("515181800001000100000000076578616d706c6503636f6d00000f0001c00c000f00010000012c0009000a046d61696cc00c");
+        // SRV, whose target is never compressed
+        assert_round_trip("525281800001000100000000045f736970045f746370076578616d706c6503636f6d0000210001c00c002100010000012c0017000a003c13c403736970076578616d706c6503636f6d00");
+        // TXT with several character-strings
+        assert_round_trip("535381800001000100000000076578616d706c6503636f6d0000100001c00c001000010000012c001e0b763d73706631202d616c6c0c7361792022686922205c6f2f0461000aff");
+        // RFC 3597 unknown type
+        assert_round_trip("545481800001000100000000076578616d706c6503636f6d00fffe0001c00cfffe00010000012c00040a000001");
+        // OPT pseudo-record in the additional section
+        assert_round_trip("656581800001000100000001076578616d706c6503636f6d0000010001c00c000100010000012c00045db8d82200002904d0000080000006fde90002abcd");
+    }
+
+    #[test]
+    fn test_encode_message_compression() {
+        // the fixture was produced by a server compressing every repeated suffix
+        let message_bytes = hex::decode("123481800001000100010001076578616d706c6503636f6d0000010001c00c0001000100000e1000045db8d822026e73c00c0001000100000e100004c0000201c02d001c000100000e10001020010db8000000000000000000000001").unwrap();
+        let message = decode_message(&message_bytes).unwrap();
+        let mut buffer: Vec<u8> = Vec::with_capacity(message_bytes.len());
+        encode_message(&message, &mut buffer).unwrap();
+        assert_eq!(buffer, message_bytes);
+    }
+
+    #[test]
+    fn test_encode_message_edns() {
+        let message_bytes = hex::decode(
+            "690601000001000000000001076578616d706c6503636f6d000001000100002904d0000000000000",
+        )
+        .unwrap();
+        let message = Message {
+            header: MessageHeader {
+                message_id: 0x6906,
+                flags: Flags {
+                    qr: QR::Query,
+                    opcode: Opcode::Query,
+                    aa: AuthoritativeAnswer::NonAuthoritative,
+                    truncated: Truncated::NotTruncated,
+                    recursion_desired: RecursionDesired::Desired,
+                    recursion_available: RecursionAvailable::NotAvailable,
+                    authentic_data: AuthenticData::NotAuthentic,
+                    checking_disabled: CheckingDisabled::NotDisabled,
+                    rcode: Rcode::NoError,
+                },
+                query_count: 1,
+                answer_count: 0,
+                name_server_count: 0,
+                additional_count: 1,
+            },
+            queries: vec![Query {
+                name: Name(vec![b"example".to_vec(), b"com".to_vec()]),
+                query_type: QueryType::A,
+                query_class: QueryClass::Internet,
+            }],
+            answers: vec![],
+            authorities: vec![],
+            additionals: vec![Edns::new(1232).into()],
+        };
+        let mut buffer: Vec<u8> = Vec::with_capacity(40);
+        encode_message(&message, &mut buffer).unwrap();
+        assert_eq!(buffer, message_bytes);
+    }
 }
@@ -1,21 +1,56 @@
 use bitvec::prelude::*;
 use dns_types::message_header::*;
-use std::io::{Error, Write};
+use std::io::{Error, ErrorKind, Write};
 
 fn encode_flags<W: Write>(flags: &Flags, writer: &mut W) -> Result<(), Error> {
     let mut flags_buffer = [0u8, 0u8];
+    let flags_bits = flags_buffer.view_bits_mut::<Msb0>();
     let qr: bool = match flags.qr {
         QR::Query => false,
         QR::Response => true,
     };
-    let flags_bits = flags_buffer.view_bits_mut::<Msb0>();
     flags_bits.set(0, qr);
-    // TODO opcode
+    let opcode: u8 = flags
+        .opcode
+        .try_into()
+        .map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+    flags_bits[1..5].store_be(opcode);
+    let aa: bool = match flags.aa {
+        AuthoritativeAnswer::Authoritative => true,
+        AuthoritativeAnswer::NonAuthoritative => false,
+    };
+    flags_bits.set(5, aa);
+    let tc: bool = match flags.truncated {
+        Truncated::Truncated => true,
+        Truncated::NotTruncated => false,
+    };
+    flags_bits.set(6, tc);
     let rd: bool = match flags.recursion_desired {
         RecursionDesired::Desired => true,
         RecursionDesired::NotDesired => false,
     };
     flags_bits.set(7, rd);
+    let ra: bool = match flags.recursion_available {
+        RecursionAvailable::Available => true,
+        RecursionAvailable::NotAvailable => false,
+    };
+    flags_bits.set(8, ra);
+    // bit 9 is the reserved Z bit and must be zero
+    let ad: bool = match flags.authentic_data {
+        AuthenticData::Authentic => true,
+        AuthenticData::NotAuthentic => false,
+    };
+    flags_bits.set(10, ad);
+    let cd: bool = match flags.checking_disabled {
+        CheckingDisabled::Disabled => true,
+        CheckingDisabled::NotDisabled => false,
+    };
+    flags_bits.set(11, cd);
+    let rcode: u8 = flags
+        .rcode
+        .try_into()
+        .map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+    flags_bits[12..16].store_be(rcode);
     writer.write_all(&flags_buffer)?;
     Ok(())
 }
@@ -54,6 +89,8 @@ mod tests {
                 truncated: Truncated::NotTruncated,
                 recursion_desired: RecursionDesired::Desired,
                 recursion_available: RecursionAvailable::NotAvailable,
+                authentic_data: AuthenticData::NotAuthentic,
+                checking_disabled: CheckingDisabled::NotDisabled,
                 rcode: Rcode::NoError,
             },
             query_count: 1,
@@ -65,4 +102,59 @@ mod tests {
         encode_message_header(&message_header, &mut buffer).unwrap();
         assert_eq!(buffer, message_header_bytes);
     }
+
+    #[test]
+    fn test_encode_flags() {
+        let flags = Flags {
+            qr: QR::Response,
+            opcode: Opcode::Status,
+            aa: AuthoritativeAnswer::Authoritative,
+            truncated: Truncated::Truncated,
+            recursion_desired: RecursionDesired::Desired,
+            recursion_available: RecursionAvailable::Available,
+            authentic_data: AuthenticData::Authentic,
+            checking_disabled: CheckingDisabled::Disabled,
+            rcode: Rcode::Refused,
+        };
+        let mut buffer: Vec<u8> = Vec::with_capacity(2);
+        encode_flags(&flags, &mut buffer).unwrap();
+        assert_eq!(buffer, vec![0x97, 0xb5]);
+    }
+
+    #[test]
+    fn test_encode_flags_extended_rcode() {
+        let flags = Flags {
+            qr: QR::Response,
+            opcode: Opcode::Query,
+            aa: AuthoritativeAnswer::NonAuthoritative,
+            truncated: Truncated::NotTruncated,
+            recursion_desired: RecursionDesired::Desired,
+            recursion_available: RecursionAvailable::Available,
+            authentic_data: AuthenticData::NotAuthentic,
+            checking_disabled: CheckingDisabled::NotDisabled,
+            rcode: Rcode::BadCookie,
+        };
+        let mut buffer: Vec<u8> = Vec::with_capacity(2);
+        let error = encode_flags(&flags, &mut buffer).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_encode_flags_opcode_out_of_range() {
+        let flags = Flags {
+            qr: QR::Query,
+            opcode: Opcode::Unknown(16),
+            aa: AuthoritativeAnswer::NonAuthoritative,
+            truncated: Truncated::NotTruncated,
+            recursion_desired: RecursionDesired::Desired,
+            recursion_available: RecursionAvailable::NotAvailable,
+            authentic_data: AuthenticData::NotAuthentic,
+            checking_disabled: CheckingDisabled::NotDisabled,
+            rcode: Rcode::NoError,
+        };
+        let mut buffer: Vec<u8> = Vec::with_capacity(2);
+        let error = encode_flags(&flags, &mut buffer).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+        assert!(buffer.is_empty());
+    }
 }
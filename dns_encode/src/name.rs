@@ -0,0 +1,127 @@
+use dns_types::Name;
+use std::{
+    collections::HashMap,
+    io::{Error, ErrorKind, Write},
+};
+
+const MAX_POINTER_OFFSET: usize = 0b0011_1111_1111_1111;
+
+/// Tracks where name suffixes have already been written in a message so that repeated
+/// suffixes can be replaced with a pointer (RFC 1035 4.1.4).
+#[derive(Debug)]
+pub struct NameCompression {
+    enabled: bool,
+    offsets: HashMap<Vec<Vec<u8>>, u16>,
+}
+
+impl NameCompression {
+    pub fn new() -> Self {
+        Self {
+            enabled: true,
+            offsets: HashMap::new(),
+        }
+    }
+
+    /// Writes every name in full, as required for canonical (DNSSEC) wire format.
+    pub fn disabled() -> Self {
+        Self {
+            enabled: false,
+            offsets: HashMap::new(),
+        }
+    }
+}
+
+impl Default for NameCompression {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Encodes a name at the end of `buffer`, which must start at the beginning of the message
+/// for compression pointers to be valid.
+pub fn encode_name(
+    name: &Name,
+    buffer: &mut Vec<u8>,
+    compression: &mut NameCompression,
+) -> Result<(), Error> {
+    name.validate()
+        .map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+    let labels = name.0.as_slice();
+    for i in 0..labels.len() {
+        if compression.enabled {
+            let suffix = &labels[i..];
+            if let Some(offset) = compression.offsets.get(suffix) {
+                let pointer = 0b1100_0000_0000_0000 | offset;
+                buffer.write_all(&pointer.to_be_bytes())?;
+                return Ok(());
+            }
+            if buffer.len() <= MAX_POINTER_OFFSET {
+                compression
+                    .offsets
+                    .insert(suffix.to_vec(), buffer.len() as u16);
+            }
+        }
+        let label_len = [labels[i].len() as u8];
+        buffer.write_all(&label_len)?;
+        buffer.write_all(&labels[i])?;
+    }
+    buffer.write_all(b"\x00")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_name() {
+        let name_bytes = hex::decode("03777777086D79646F6D61696E03636F6D00").unwrap();
+        let name = Name(vec![b"www".to_vec(), b"mydomain".to_vec(), b"com".to_vec()]);
+        let mut buffer: Vec<u8> = Vec::with_capacity(18);
+        encode_name(&name, &mut buffer, &mut NameCompression::new()).unwrap();
+        assert_eq!(buffer, name_bytes);
+    }
+
+    #[test]
+    fn test_encode_name_compression() {
+        let name_bytes =
+            hex::decode("03777777086D79646F6D61696E03636F6D00036D7878C004C000").unwrap();
+        let first = Name(vec![b"www".to_vec(), b"mydomain".to_vec(), b"com".to_vec()]);
+        let second = Name(vec![b"mxx".to_vec(), b"mydomain".to_vec(), b"com".to_vec()]);
+        let mut compression = NameCompression::new();
+        let mut buffer: Vec<u8> = Vec::with_capacity(26);
+        encode_name(&first, &mut buffer, &mut compression).unwrap();
+        encode_name(&second, &mut buffer, &mut compression).unwrap();
+        encode_name(&first, &mut buffer, &mut compression).unwrap();
+        assert_eq!(buffer, name_bytes);
+    }
+
+    #[test]
+    fn test_encode_name_compression_disabled() {
+        let name_bytes =
+            hex::decode("03777777086D79646F6D61696E03636F6D0003777777086D79646F6D61696E03636F6D00")
+                .unwrap();
+        let name = Name(vec![b"www".to_vec(), b"mydomain".to_vec(), b"com".to_vec()]);
+        let mut compression = NameCompression::disabled();
+        let mut buffer: Vec<u8> = Vec::with_capacity(36);
+        encode_name(&name, &mut buffer, &mut compression).unwrap();
+        encode_name(&name, &mut buffer, &mut compression).unwrap();
+        assert_eq!(buffer, name_bytes);
+    }
+
+    #[test]
+    fn test_encode_name_invalid() {
+        let names = [
+            Name(vec![b"www".to_vec(), vec![], b"com".to_vec()]),
+            Name(vec![vec![b'a'; 64], b"com".to_vec()]),
+            Name(vec![vec![b'a'; 256]]),
+            Name(vec![vec![b'a'; 63]; 4]),
+        ];
+        for name in names {
+            let mut buffer: Vec<u8> = Vec::new();
+            let error = encode_name(&name, &mut buffer, &mut NameCompression::new()).unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::InvalidData);
+            assert!(buffer.is_empty());
+        }
+    }
+}
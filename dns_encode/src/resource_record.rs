@@ -0,0 +1,168 @@
+use crate::edns::encode_edns_options;
+use crate::name::{encode_name, NameCompression};
+use dns_types::resource_record::*;
+use std::io::{Error, ErrorKind, Write};
+
+/// Names embedded in the rdata of the well-known RFC 1035 types may be compressed
+/// against `compression`; names in any other type must be written in full (RFC 3597 4).
+fn encode_resource_data(
+    rdata: &ResourceData,
+    buffer: &mut Vec<u8>,
+    compression: &mut NameCompression,
+) -> Result<(), Error> {
+    match rdata {
+        ResourceData::A(ip) => buffer.write_all(&ip.octets()),
+        ResourceData::AAAA(ip) => buffer.write_all(&ip.octets()),
+        ResourceData::NS(name) => encode_name(name, buffer, compression),
+        ResourceData::CNAME(name) => encode_name(name, buffer, compression),
+        ResourceData::PTR(name) => encode_name(name, buffer, compression),
+        ResourceData::DNAME(name) => encode_name(name, buffer, &mut NameCompression::disabled()),
+        ResourceData::SOA {
+            mname,
+            rname,
+            serial,
+            refresh,
+            retry,
+            expire,
+            minimum,
+        } => {
+            encode_name(mname, buffer, compression)?;
+            encode_name(rname, buffer, compression)?;
+            for value in [serial, refresh, retry, expire, minimum] {
+                buffer.write_all(&value.to_be_bytes())?;
+            }
+            Ok(())
+        }
+        ResourceData::MX {
+            preference,
+            exchange,
+        } => {
+            buffer.write_all(&preference.to_be_bytes())?;
+            encode_name(exchange, buffer, compression)
+        }
+        ResourceData::SRV {
+            priority,
+            weight,
+            port,
+            target,
+        } => {
+            buffer.write_all(&priority.to_be_bytes())?;
+            buffer.write_all(&weight.to_be_bytes())?;
+            buffer.write_all(&port.to_be_bytes())?;
+            encode_name(target, buffer, &mut NameCompression::disabled())
+        }
+        ResourceData::TXT(strings) => encode_character_strings(strings, buffer),
+        ResourceData::SPF(strings) => encode_character_strings(strings, buffer),
+        ResourceData::OPT(options) => encode_edns_options(options, buffer),
+        ResourceData::Unknown { data, .. } => buffer.write_all(data),
+    }
+}
+
+fn encode_character_strings(strings: &[Vec<u8>], buffer: &mut Vec<u8>) -> Result<(), Error> {
+    for string in strings {
+        let string_len = u8::try_from(string.len()).map_err(|_| {
+            Error::new(ErrorKind::InvalidData, "character-string exceeds 255 bytes")
+        })?;
+        buffer.write_all(&[string_len])?;
+        buffer.write_all(string)?;
+    }
+    Ok(())
+}
+
+pub fn encode_resource_record(
+    resource_record: &ResourceRecord,
+    buffer: &mut Vec<u8>,
+    compression: &mut NameCompression,
+) -> Result<(), Error> {
+    encode_name(&resource_record.name, buffer, compression)?;
+    let rtype: u16 = resource_record.resource_type.into();
+    buffer.write_all(&rtype.to_be_bytes())?;
+    let rclass: u16 = resource_record.resource_class.into();
+    buffer.write_all(&rclass.to_be_bytes())?;
+    buffer.write_all(&resource_record.ttl.to_be_bytes())?;
+
+    // RDLENGTH is only known once the rdata has been written, so reserve it and patch it after
+    let rdlength_offset = buffer.len();
+    buffer.write_all(&[0u8, 0u8])?;
+    encode_resource_data(&resource_record.rdata, buffer, compression)?;
+    let rdlength = u16::try_from(buffer.len() - rdlength_offset - 2)
+        .map_err(|_| Error::new(ErrorKind::InvalidData, "resource data exceeds 65535 bytes"))?;
+    buffer[rdlength_offset..rdlength_offset + 2].copy_from_slice(&rdlength.to_be_bytes());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dns_types::Name;
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    #[test]
+    fn test_encode_resource_record_a_record() {
+        let resource_record_bytes =
+            hex::decode("076578616d706c6503636f6d000001000100005a0200045db8d822").unwrap();
+        let resource_record = ResourceRecord {
+            name: Name(vec![b"example".to_vec(), b"com".to_vec()]),
+            resource_type: ResourceType::A,
+            resource_class: ResourceClass::Internet,
+            ttl: 23042,
+            rdata: ResourceData::A(Ipv4Addr::from(0x5db8d822)),
+        };
+        let mut buffer: Vec<u8> = Vec::with_capacity(27);
+        encode_resource_record(&resource_record, &mut buffer, &mut NameCompression::new()).unwrap();
+        assert_eq!(buffer, resource_record_bytes);
+    }
+
+    #[test]
+    fn test_encode_resource_record_aaaa_record() {
+        let resource_record_bytes = hex::decode(
+            "076578616d706c6503636f6d00001c0001000130c8001026062800022000010248189325c81946",
+        )
+        .unwrap();
+        let resource_record = ResourceRecord {
+            name: Name(vec![b"example".to_vec(), b"com".to_vec()]),
+            resource_type: ResourceType::AAAA,
+            resource_class: ResourceClass::Internet,
+            ttl: 78024,
+            rdata: ResourceData::AAAA(Ipv6Addr::from([
+                0x2606, 0x2800, 0x0220, 0x0001, 0x0248, 0x1893, 0x25c8, 0x1946,
+            ])),
+        };
+        let mut buffer: Vec<u8> = Vec::with_capacity(39);
+        encode_resource_record(&resource_record, &mut buffer, &mut NameCompression::new()).unwrap();
+        assert_eq!(buffer, resource_record_bytes);
+    }
+
+    #[test]
+    fn test_encode_resource_record_cname_record() {
+        let resource_record_bytes =
+            hex::decode("037777770667697468756203636f6d000005000100000e100002c004").unwrap();
+        let resource_record = ResourceRecord {
+            name: Name(vec![b"www".to_vec(), b"github".to_vec(), b"com".to_vec()]),
+            resource_type: ResourceType::CNAME,
+            resource_class: ResourceClass::Internet,
+            ttl: 3600,
+            rdata: ResourceData::CNAME(Name(vec![b"github".to_vec(), b"com".to_vec()])),
+        };
+        let mut buffer: Vec<u8> = Vec::with_capacity(30);
+        encode_resource_record(&resource_record, &mut buffer, &mut NameCompression::new()).unwrap();
+        assert_eq!(buffer, resource_record_bytes);
+    }
+
+    #[test]
+    fn test_encode_resource_record_dname_record_uncompressed() {
+        let resource_record_bytes =
+            hex::decode("076578616d706c6503636f6d000027000100000e10000d076578616d706c65036e657400")
+                .unwrap();
+        let resource_record = ResourceRecord {
+            name: Name(vec![b"example".to_vec(), b"com".to_vec()]),
+            resource_type: ResourceType::DNAME,
+            resource_class: ResourceClass::Internet,
+            ttl: 3600,
+            rdata: ResourceData::DNAME(Name(vec![b"example".to_vec(), b"net".to_vec()])),
+        };
+        let mut buffer: Vec<u8> = Vec::with_capacity(38);
+        encode_resource_record(&resource_record, &mut buffer, &mut NameCompression::new()).unwrap();
+        assert_eq!(buffer, resource_record_bytes);
+    }
+}
@@ -0,0 +1,341 @@
+use crate::transport::{exchange_tcp, exchange_udp};
+use dns_decode::{decode_message, DecodeError};
+use dns_encode::encode_message;
+use dns_types::*;
+use std::net::SocketAddr;
+use std::time::Duration;
+use thiserror::Error;
+
+/// Largest UDP message a server may send without EDNS (RFC 1035 2.3.4)
+pub const DNS_MAX_BUFFER_SIZE: usize = 512;
+/// The TC bit in the third byte of the header
+const TC_MASK: u8 = 0b0000_0010;
+
+#[derive(Debug, Clone, Copy)]
+pub struct ExchangeOptions {
+    /// Time to wait for an answer in the first round, doubled for each following round
+    pub timeout: Duration,
+    /// Rounds over the whole server list before giving up
+    pub tries: u32,
+    /// Start each query at a different server instead of always at the first
+    pub rotate: bool,
+    /// Query over TCP instead of UDP
+    pub tcp: bool,
+    pub receive_buffer_size: usize,
+}
+
+impl Default for ExchangeOptions {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(5),
+            tries: 3,
+            rotate: false,
+            tcp: false,
+            receive_buffer_size: DNS_MAX_BUFFER_SIZE,
+        }
+    }
+}
+
+/// A response along with how it was obtained
+#[derive(Debug)]
+pub struct Exchange {
+    pub response: Message,
+    /// The UDP response was truncated and the query was repeated over TCP
+    pub retried_over_tcp: bool,
+}
+
+#[derive(Error, Debug)]
+pub enum AttemptError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("invalid response: {0}")]
+    Decode(#[from] DecodeError),
+    #[error("response id {received} does not match query id {expected}")]
+    IdMismatch { expected: u16, received: u16 },
+}
+
+#[derive(Error, Debug)]
+pub enum ExchangeError {
+    #[error("could not encode query: {0}")]
+    Encode(std::io::Error),
+    /// Every failed attempt, in the order they were made
+    #[error("no server could be reached{}", list_failures(.0))]
+    AllServersFailed(Vec<(SocketAddr, AttemptError)>),
+}
+
+fn list_failures(failures: &[(SocketAddr, AttemptError)]) -> String {
+    failures
+        .iter()
+        .map(|(server, error)| format!("\n  {}: {}", server, error))
+        .collect()
+}
+
+/// Sends `request` to each of `servers` in turn until one answers, retrying the list
+/// `options.tries` times with a doubling timeout
+pub fn exchange(
+    request: &Message,
+    servers: &[SocketAddr],
+    options: &ExchangeOptions,
+) -> Result<Exchange, ExchangeError> {
+    let mut request_buffer: Vec<u8> = Vec::with_capacity(DNS_MAX_BUFFER_SIZE);
+    encode_message(request, &mut request_buffer).map_err(ExchangeError::Encode)?;
+    let mut failures = vec![];
+    let start = match options.rotate {
+        true if !servers.is_empty() => request.header.message_id as usize % servers.len(),
+        _ => 0,
+    };
+    for round in 0..options.tries {
+        let timeout = options.timeout.saturating_mul(2u32.saturating_pow(round));
+        for &server in servers.iter().cycle().skip(start).take(servers.len()) {
+            match attempt(server, &request_buffer, request, options, timeout) {
+                Ok(response) => return Ok(response),
+                Err(error) => failures.push((server, error)),
+            }
+        }
+    }
+    Err(ExchangeError::AllServersFailed(failures))
+}
+
+fn attempt(
+    server: SocketAddr,
+    request_buffer: &[u8],
+    request: &Message,
+    options: &ExchangeOptions,
+    timeout: Duration,
+) -> Result<Exchange, AttemptError> {
+    let mut response = if options.tcp {
+        exchange_tcp(server, request_buffer, timeout)?
+    } else {
+        exchange_udp(
+            server,
+            request_buffer,
+            options.receive_buffer_size,
+            timeout,
+            |datagram| is_response(datagram, request),
+        )?
+    };
+    // a truncated answer may be cut off in the middle of a record, so the TC bit is read
+    // before decoding
+    let retried_over_tcp =
+        !options.tcp && response.get(2).is_some_and(|flags| flags & TC_MASK != 0);
+    if retried_over_tcp {
+        response = exchange_tcp(server, request_buffer, timeout)?;
+    }
+    let response = decode_message(&response)?;
+    if response.header.message_id != request.header.message_id {
+        return Err(AttemptError::IdMismatch {
+            expected: request.header.message_id,
+            received: response.header.message_id,
+        });
+    }
+    Ok(Exchange {
+        response,
+        retried_over_tcp,
+    })
+}
+
+/// Whether a datagram answers `request` rather than being a stray or forged one: the id must
+/// match, and so must the question unless it is left out of a FORMERR
+fn is_response(datagram: &[u8], request: &Message) -> bool {
+    if datagram.get(..2) != Some(&request.header.message_id.to_be_bytes()[..]) {
+        return false;
+    }
+    match decode_message(datagram) {
+        Ok(response) => {
+            response.queries == request.queries
+                || (response.queries.is_empty()
+                    && response.header.flags.rcode == Rcode::FormatError)
+        }
+        // malformed answers are reported by the caller
+        Err(_) => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::{TcpListener, UdpSocket};
+    use std::thread;
+
+    fn query(message_id: u16) -> Message {
+        Message {
+            header: MessageHeader {
+                message_id,
+                flags: Flags {
+                    qr: QR::Query,
+                    opcode: Opcode::Query,
+                    aa: AuthoritativeAnswer::NonAuthoritative,
+                    truncated: Truncated::NotTruncated,
+                    recursion_desired: RecursionDesired::Desired,
+                    recursion_available: RecursionAvailable::NotAvailable,
+                    authentic_data: AuthenticData::NotAuthentic,
+                    checking_disabled: CheckingDisabled::NotDisabled,
+                    rcode: Rcode::NoError,
+                },
+                query_count: 1,
+                answer_count: 0,
+                name_server_count: 0,
+                additional_count: 0,
+            },
+            queries: vec![Query {
+                name: "example.com".parse().unwrap(),
+                query_type: QueryType::A,
+                query_class: QueryClass::Internet,
+            }],
+            answers: vec![],
+            authorities: vec![],
+            additionals: vec![],
+        }
+    }
+
+    /// A server that echoes each query back with the QR bit set
+    fn echo_server() -> SocketAddr {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let address = socket.local_addr().unwrap();
+        thread::spawn(move || {
+            let mut buffer = [0u8; 512];
+            while let Ok((size, peer)) = socket.recv_from(&mut buffer) {
+                buffer[2] |= 0x80;
+                socket.send_to(&buffer[..size], peer).unwrap();
+            }
+        });
+        address
+    }
+
+    /// A server that first answers each query with a forged id and a forged question, then
+    /// echoes it back
+    fn spoofed_server() -> SocketAddr {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let address = socket.local_addr().unwrap();
+        thread::spawn(move || {
+            let mut buffer = [0u8; 512];
+            while let Ok((size, peer)) = socket.recv_from(&mut buffer) {
+                buffer[2] |= 0x80;
+                let mut forged = buffer[..size].to_vec();
+                forged[0] ^= 0xff;
+                socket.send_to(&forged, peer).unwrap();
+                let mut forged = buffer[..size].to_vec();
+                // "example.com" becomes "example.org"
+                forged[21..24].copy_from_slice(b"org");
+                socket.send_to(&forged, peer).unwrap();
+                socket.send_to(&buffer[..size], peer).unwrap();
+            }
+        });
+        address
+    }
+
+    /// A server that answers over UDP with a truncated response cut off inside its answer
+    /// record, and echoes the query back over TCP
+    fn truncating_server() -> SocketAddr {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let address = socket.local_addr().unwrap();
+        let listener = TcpListener::bind(address).unwrap();
+        thread::spawn(move || {
+            let mut buffer = [0u8; 512];
+            let (size, peer) = socket.recv_from(&mut buffer).unwrap();
+            let mut truncated = buffer[..size].to_vec();
+            truncated[2] |= 0x80 | TC_MASK;
+            truncated[7] = 1;
+            truncated.extend_from_slice(&[0xc0, 0x0c, 0x00, 0x01, 0x00]);
+            socket.send_to(&truncated, peer).unwrap();
+        });
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut length = [0u8; 2];
+            stream.read_exact(&mut length).unwrap();
+            let mut request = vec![0u8; u16::from_be_bytes(length) as usize];
+            stream.read_exact(&mut request).unwrap();
+            request[2] |= 0x80;
+            stream.write_all(&length).unwrap();
+            stream.write_all(&request).unwrap();
+        });
+        address
+    }
+
+    /// A bound socket that never answers, kept alive by the returned handle
+    fn silent_server() -> (UdpSocket, SocketAddr) {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let address = socket.local_addr().unwrap();
+        (socket, address)
+    }
+
+    fn failed_servers(error: ExchangeError) -> Vec<SocketAddr> {
+        match error {
+            ExchangeError::AllServersFailed(failures) => {
+                failures.into_iter().map(|(server, _)| server).collect()
+            }
+            error => panic!("unexpected error {}", error),
+        }
+    }
+
+    fn options() -> ExchangeOptions {
+        ExchangeOptions {
+            timeout: Duration::from_millis(50),
+            tries: 2,
+            ..ExchangeOptions::default()
+        }
+    }
+
+    #[test]
+    fn test_exchange_fails_over_to_next_server() {
+        let (_silent, silent) = silent_server();
+        let echo = echo_server();
+        let response = exchange(&query(0x1234), &[silent, echo], &options())
+            .unwrap()
+            .response;
+        assert_eq!(response.header.message_id, 0x1234);
+        assert_eq!(response.header.flags.qr, QR::Response);
+    }
+
+    #[test]
+    fn test_exchange_ignores_forged_responses() {
+        let server = spoofed_server();
+        let options = ExchangeOptions {
+            tries: 1,
+            ..options()
+        };
+        let response = exchange(&query(0x1234), &[server], &options)
+            .unwrap()
+            .response;
+        assert_eq!(response.header.message_id, 0x1234);
+        assert_eq!(response.queries, query(0x1234).queries);
+    }
+
+    #[test]
+    fn test_exchange_retries_cut_off_truncated_response_over_tcp() {
+        let server = truncating_server();
+        let exchange = exchange(&query(0x1234), &[server], &options()).unwrap();
+        assert!(exchange.retried_over_tcp);
+        assert_eq!(exchange.response.header.message_id, 0x1234);
+        assert_eq!(
+            exchange.response.header.flags.truncated,
+            Truncated::NotTruncated
+        );
+    }
+
+    #[test]
+    fn test_exchange_reports_every_failure() {
+        let (_first, first) = silent_server();
+        let (_second, second) = silent_server();
+        let error = exchange(&query(0x1234), &[first, second], &options()).unwrap_err();
+        assert!(error.to_string().starts_with(&format!(
+            "no server could be reached\n  {}: timed out",
+            first
+        )));
+        assert_eq!(failed_servers(error), vec![first, second, first, second]);
+    }
+
+    #[test]
+    fn test_exchange_rotate_starts_at_message_id() {
+        let (_first, first) = silent_server();
+        let (_second, second) = silent_server();
+        let options = ExchangeOptions {
+            tries: 1,
+            rotate: true,
+            ..options()
+        };
+        let error = exchange(&query(1), &[first, second], &options).unwrap_err();
+        assert_eq!(failed_servers(error), vec![second, first]);
+    }
+}
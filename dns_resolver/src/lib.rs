@@ -0,0 +1,5 @@
+pub mod resolv_conf;
+pub mod search;
+
+pub use resolv_conf::ResolvConf;
+pub use search::search_names;
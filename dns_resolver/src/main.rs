@@ -1,66 +1,240 @@
-use anyhow::Result;
-use clap::{Parser, ValueEnum};
-use dns_decode::{decode_domain_name, decode_message};
-use dns_encode::encode_message;
+use anyhow::{Context, Result};
+use clap::Parser;
+use client::{exchange, ExchangeOptions, DNS_MAX_BUFFER_SIZE};
+use dns_resolver::{search_names, ResolvConf};
 use dns_types::*;
-use std::net::{Ipv4Addr, UdpSocket};
+use std::net::{AddrParseError, IpAddr, SocketAddr};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+mod client;
+mod transport;
 
 #[derive(Parser)]
 #[command(about, long_about = None)]
 struct Cli {
-    domain_name: String,
-    #[arg(short, long, default_value_t = Ipv4Addr::from([1,1,1,1]))]
-    server: Ipv4Addr,
-    #[arg(short, long, value_enum, default_value_t = ResourceType::A)]
-    resource_type: ResourceType,
+    /// Name to look up, expanded with the search list unless it ends with a dot
+    #[arg(required_unless_present = "reverse")]
+    domain_name: Option<QueryName>,
+    /// Reverse lookup: query the PTR record of this address
+    #[arg(short = 'x', conflicts_with = "domain_name")]
+    reverse: Option<IpAddr>,
+    /// Servers to query in order instead of the resolv.conf nameservers, repeat the option or
+    /// separate them with commas. Each is an address such as 1.1.1.1 or 2606:4700::1111,
+    /// optionally with a port as in [::1]:5353
+    #[arg(short, long, value_delimiter = ',')]
+    server: Vec<Server>,
+    /// Port for servers given without one
+    #[arg(short, long, default_value_t = DNS_PORT)]
+    port: u16,
+    /// Record type to query, a mnemonic such as MX or the generic TYPE<n>
+    #[arg(short, long, default_value_t = QueryType::A)]
+    resource_type: QueryType,
+    /// Advertise this UDP payload size with an EDNS(0) OPT record
+    #[arg(long)]
+    bufsize: Option<u16>,
+    /// Query over TCP instead of UDP
+    #[arg(long)]
+    tcp: bool,
+    /// Seconds to wait for an answer, doubled for every round over the servers
+    /// [default: resolv.conf timeout]
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    timeout: Option<u64>,
+    /// Rounds over the server list before giving up [default: resolv.conf attempts]
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    tries: Option<u32>,
+    /// Start at a different server for every query
+    #[arg(long)]
+    rotate: bool,
+    /// Read nameservers and options from this file instead of /etc/resolv.conf
+    #[arg(long, value_name = "PATH")]
+    resolv_conf: Option<PathBuf>,
+    /// Search domains instead of the resolv.conf ones, separated by commas, `.` for none
+    #[arg(long, value_delimiter = ',')]
+    search: Vec<Name>,
+    /// Names with at least this many dots are tried as is before the search domains
+    /// [default: resolv.conf ndots]
+    #[arg(long)]
+    ndots: Option<u8>,
+    /// Print each name as it is tried
+    #[arg(short, long)]
+    verbose: bool,
+}
+
+/// A name as given on the command line, remembering whether it was absolute
+#[derive(Clone, Debug)]
+struct QueryName {
+    name: Name,
+    absolute: bool,
 }
 
-#[derive(ValueEnum, Clone, Debug)]
-#[clap(rename_all = "verbatim")]
-enum ResourceType {
-    A,
-    AAAA,
+impl FromStr for QueryName {
+    type Err = ParseNameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, absolute) = Name::from_str_partial(s)?;
+        Ok(QueryName { name, absolute })
+    }
 }
 
-impl Into<QueryType> for ResourceType {
-    fn into(self) -> QueryType {
-        match self {
-            ResourceType::A => QueryType::A,
-            ResourceType::AAAA => QueryType::AAAA,
+/// A server address with an optional port
+#[derive(Clone, Debug)]
+struct Server {
+    ip: IpAddr,
+    port: Option<u16>,
+}
+
+impl FromStr for Server {
+    type Err = AddrParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(address) = s.parse::<SocketAddr>() {
+            return Ok(Server {
+                ip: address.ip(),
+                port: Some(address.port()),
+            });
         }
+        let ip = s
+            .strip_prefix('[')
+            .and_then(|s| s.strip_suffix(']'))
+            .unwrap_or(s);
+        Ok(Server {
+            ip: ip.parse()?,
+            port: None,
+        })
     }
 }
 
 const DNS_PORT: u16 = 53;
-const DNS_MAX_BUFFER_SIZE: usize = 512;
+/// UDP payload size advertised when resolv.conf enables edns0 (DNS flag day 2020)
+const EDNS_BUFFER_SIZE: u16 = 1232;
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
+    let conf = match cli.resolv_conf.as_ref() {
+        Some(path) => ResolvConf::from_file(path)
+            .with_context(|| format!("could not read {}", path.display()))?,
+        None => ResolvConf::system(),
+    };
+    let bufsize = cli.bufsize.or(conf.edns0.then_some(EDNS_BUFFER_SIZE));
 
-    let socket = UdpSocket::bind("0.0.0.0:0")?;
-
-    let mut send_buffer: Vec<u8> = Vec::with_capacity(DNS_MAX_BUFFER_SIZE);
-    let message = create_message(&cli.domain_name, cli.resource_type, rand::random())?;
-    encode_message(&message, &mut send_buffer)?;
-
-    socket.connect((cli.server, DNS_PORT))?;
-    socket.send(&send_buffer)?;
+    let (name, names, query_type) = match (cli.domain_name, cli.reverse) {
+        (_, Some(ip)) => (Name::from_ip(ip), vec![Name::from_ip(ip)], QueryType::PTR),
+        (Some(QueryName { name, absolute }), None) => {
+            let search = match cli.search.as_slice() {
+                [] => conf.search,
+                search => search
+                    .iter()
+                    .filter(|domain| !domain.is_root())
+                    .cloned()
+                    .collect(),
+            };
+            let ndots = cli.ndots.unwrap_or(conf.ndots);
+            let names = search_names(&name, absolute, &search, ndots);
+            (name, names, cli.resource_type)
+        }
+        (None, None) => unreachable!("clap requires a domain name unless -x is present"),
+    };
 
-    let mut receive_buffer = [0u8; DNS_MAX_BUFFER_SIZE];
-    let received_size = socket.recv(&mut receive_buffer)?;
-    let decoded_response = decode_message(&receive_buffer[0..received_size])?;
+    let servers: Vec<SocketAddr> = if cli.server.is_empty() {
+        conf.nameservers
+            .iter()
+            .map(|&ip| SocketAddr::new(ip, cli.port))
+            .collect()
+    } else {
+        cli.server
+            .iter()
+            .map(|server| SocketAddr::new(server.ip, server.port.unwrap_or(cli.port)))
+            .collect()
+    };
+    let options = ExchangeOptions {
+        timeout: Duration::from_secs(cli.timeout.unwrap_or(conf.timeout as u64)),
+        tries: cli.tries.unwrap_or(conf.attempts),
+        rotate: cli.rotate || conf.rotate,
+        tcp: cli.tcp,
+        receive_buffer_size: match bufsize {
+            Some(bufsize) => DNS_MAX_BUFFER_SIZE.max(bufsize as usize),
+            None => DNS_MAX_BUFFER_SIZE,
+        },
+    };
+    let query = Query {
+        name,
+        query_type,
+        query_class: QueryClass::Internet,
+    };
+    let decoded_response = resolve(&query, names, bufsize, &servers, &options, cli.verbose)?;
     println!("{}", decoded_response);
     Ok(())
 }
 
-fn create_message<QT: Into<QueryType>>(
-    domain_name: &str,
-    query_type: QT,
+/// Queries each of `names` in turn like glibc's res_search: NXDOMAIN, NODATA and SERVFAIL
+/// answers move on to the next name, as does any failure of a name tried as is before the
+/// search domains, even when no server could be reached. When no name has an answer the
+/// response for the name tried as is first wins, then the first NODATA response, then the
+/// first SERVFAIL, then the last response.
+fn resolve(
+    query: &Query,
+    names: Vec<Name>,
+    bufsize: Option<u16>,
+    servers: &[SocketAddr],
+    options: &ExchangeOptions,
+    verbose: bool,
+) -> Result<Message> {
+    let as_is_first = names.len() > 1 && names[0].eq_case_sensitive(&query.name);
+    let mut responses: Vec<Message> = Vec::with_capacity(names.len());
+    let (mut as_is, mut nodata, mut servfail) = (None, None, None);
+    for (index, name) in names.into_iter().enumerate() {
+        if verbose {
+            println!(";; Trying {}", name);
+        }
+        let tried_as_is = as_is_first && index == 0;
+        let message = create_message(name, query.query_type, bufsize, rand::random())?;
+        let response = match exchange(&message, servers, options) {
+            Ok(exchange) => {
+                if exchange.retried_over_tcp {
+                    println!(";; Truncated, retrying in TCP mode.");
+                }
+                exchange.response
+            }
+            Err(error) if tried_as_is => {
+                if verbose {
+                    println!(";; {}", error);
+                }
+                continue;
+            }
+            Err(error) => return Err(error.into()),
+        };
+        match response.rcode() {
+            Rcode::NoError if !response.answers.is_empty() => return Ok(response),
+            Rcode::NoError => {
+                nodata.get_or_insert(responses.len());
+            }
+            Rcode::ServerFailure => {
+                servfail.get_or_insert(responses.len());
+            }
+            Rcode::NameError => {}
+            _ if !tried_as_is => return Ok(response),
+            _ => {}
+        }
+        if tried_as_is {
+            as_is = Some(responses.len());
+        }
+        responses.push(response);
+    }
+    let index = as_is.or(nodata).or(servfail).unwrap_or(responses.len() - 1);
+    Ok(responses.swap_remove(index))
+}
+
+fn create_message(
+    domain_name: Name,
+    query_type: QueryType,
+    bufsize: Option<u16>,
     message_id: u16,
 ) -> Result<Message> {
-    let query_name = decode_domain_name(&domain_name)?
-        .iter()
-        .map(|s| s.to_string())
+    let additionals: Vec<ResourceRecord> = bufsize
+        .map(|bufsize| Edns::new(bufsize).into())
+        .into_iter()
         .collect();
     let message = Message {
         header: MessageHeader {
@@ -72,19 +246,176 @@ fn create_message<QT: Into<QueryType>>(
                 truncated: Truncated::NotTruncated,
                 recursion_desired: RecursionDesired::Desired,
                 recursion_available: RecursionAvailable::NotAvailable,
+                authentic_data: AuthenticData::NotAuthentic,
+                checking_disabled: CheckingDisabled::NotDisabled,
                 rcode: Rcode::NoError,
             },
             query_count: 1,
             answer_count: 0,
             name_server_count: 0,
-            additional_count: 0,
+            additional_count: additionals.len() as u16,
         },
         queries: vec![Query {
-            name: Name(query_name),
-            query_type: query_type.into(),
+            name: domain_name,
+            query_type,
             query_class: QueryClass::Internet,
         }],
         answers: vec![],
+        authorities: vec![],
+        additionals,
     };
     Ok(message)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_server_from_str() {
+        let parse = |s: &str| s.parse::<Server>().map(|server| (server.ip, server.port));
+        assert_eq!(parse("1.1.1.1"), Ok(([1, 1, 1, 1].into(), None)));
+        assert_eq!(
+            parse("127.0.0.1:5353"),
+            Ok(([127, 0, 0, 1].into(), Some(5353)))
+        );
+        assert_eq!(
+            parse("::1"),
+            Ok((IpAddr::from([0, 0, 0, 0, 0, 0, 0, 1u16]), None))
+        );
+        assert_eq!(
+            parse("[2606:4700::1111]"),
+            Ok(("2606:4700::1111".parse().unwrap(), None))
+        );
+        assert_eq!(
+            parse("[2606:4700::1111]:53"),
+            Ok(("2606:4700::1111".parse().unwrap(), Some(53)))
+        );
+        assert!(parse("example.com").is_err());
+    }
+
+    /// A server answering each query with the rcode and answers `respond` picks for its name,
+    /// or not at all if it picks `None`
+    fn fake_server(respond: fn(&str) -> Option<(Rcode, bool)>) -> SocketAddr {
+        let socket = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let address = socket.local_addr().unwrap();
+        std::thread::spawn(move || {
+            let mut buffer = [0u8; 512];
+            while let Ok((size, peer)) = socket.recv_from(&mut buffer) {
+                let mut message = dns_decode::decode_message(&buffer[..size]).unwrap();
+                let name = message.queries[0].name.to_string();
+                let Some((rcode, answer)) = respond(&name) else {
+                    continue;
+                };
+                message.header.flags.qr = QR::Response;
+                message.header.flags.rcode = rcode;
+                if answer {
+                    message.answers.push(ResourceRecord {
+                        name: message.queries[0].name.clone(),
+                        resource_type: ResourceType::A,
+                        resource_class: ResourceClass::Internet,
+                        ttl: 60,
+                        rdata: ResourceData::A([192, 0, 2, 1].into()),
+                    });
+                    message.header.answer_count = 1;
+                }
+                let mut response = vec![];
+                dns_encode::encode_message(&message, &mut response).unwrap();
+                socket.send_to(&response, peer).unwrap();
+            }
+        });
+        address
+    }
+
+    fn resolve_with(server: SocketAddr, name: &str, search: &[&str]) -> Message {
+        let (name, absolute) = Name::from_str_partial(name).unwrap();
+        let search: Vec<Name> = search
+            .iter()
+            .map(|domain| domain.parse().unwrap())
+            .collect();
+        let names = search_names(&name, absolute, &search, 1);
+        let query = Query {
+            name,
+            query_type: QueryType::A,
+            query_class: QueryClass::Internet,
+        };
+        let options = ExchangeOptions {
+            timeout: Duration::from_secs(1),
+            tries: 1,
+            ..ExchangeOptions::default()
+        };
+        resolve(&query, names, None, &[server], &options, false).unwrap()
+    }
+
+    #[test]
+    fn test_resolve_returns_first_answer() {
+        let server = fake_server(|name| match name {
+            "api.b.example." => Some((Rcode::NoError, true)),
+            _ => Some((Rcode::NameError, false)),
+        });
+        let response = resolve_with(server, "api", &["a.example", "b.example"]);
+        assert_eq!(response.queries[0].name.to_string(), "api.b.example.");
+        assert_eq!(response.answers.len(), 1);
+    }
+
+    #[test]
+    fn test_resolve_prefers_nodata_over_nxdomain() {
+        let server = fake_server(|name| match name {
+            "api.a.example." => Some((Rcode::NoError, false)),
+            "api.b.example." => Some((Rcode::ServerFailure, false)),
+            _ => Some((Rcode::NameError, false)),
+        });
+        let response = resolve_with(server, "api", &["a.example", "b.example"]);
+        assert_eq!(response.queries[0].name.to_string(), "api.a.example.");
+        assert_eq!(response.rcode(), Rcode::NoError);
+    }
+
+    #[test]
+    fn test_resolve_continues_after_failure_as_is() {
+        let server = fake_server(|name| match name {
+            "www.example." => Some((Rcode::Refused, false)),
+            "www.example.corp.example." => Some((Rcode::NoError, true)),
+            _ => Some((Rcode::NameError, false)),
+        });
+        let response = resolve_with(server, "www.example", &["corp.example"]);
+        assert_eq!(
+            response.queries[0].name.to_string(),
+            "www.example.corp.example."
+        );
+
+        let server = fake_server(|name| match name {
+            "www.example." => Some((Rcode::Refused, false)),
+            _ => Some((Rcode::NameError, false)),
+        });
+        let response = resolve_with(server, "www.example", &["corp.example"]);
+        assert_eq!(response.rcode(), Rcode::Refused);
+
+        let server = fake_server(|name| match name {
+            "www.example." => None,
+            "www.example.corp.example." => Some((Rcode::NoError, true)),
+            _ => Some((Rcode::NameError, false)),
+        });
+        let response = resolve_with(server, "www.example", &["corp.example"]);
+        assert_eq!(response.answers.len(), 1);
+    }
+
+    #[test]
+    fn test_resolve_prefers_response_as_is_when_tried_first() {
+        let server = fake_server(|name| match name {
+            "www.example." => Some((Rcode::NameError, false)),
+            _ => Some((Rcode::NoError, false)),
+        });
+        let response = resolve_with(server, "www.example", &["corp.example"]);
+        assert_eq!(response.queries[0].name.to_string(), "www.example.");
+        assert_eq!(response.rcode(), Rcode::NameError);
+
+        // tried last, the name as is has no priority over a SERVFAIL
+        let server = fake_server(|name| match name {
+            "api." => Some((Rcode::NameError, false)),
+            _ => Some((Rcode::ServerFailure, false)),
+        });
+        let response = resolve_with(server, "api", &["a.example"]);
+        assert_eq!(response.queries[0].name.to_string(), "api.a.example.");
+        assert_eq!(response.rcode(), Rcode::ServerFailure);
+    }
+}
@@ -0,0 +1,174 @@
+use dns_types::Name;
+use std::io::Error;
+use std::net::{IpAddr, Ipv4Addr};
+use std::path::Path;
+
+pub const DEFAULT_PATH: &str = "/etc/resolv.conf";
+
+// limits applied by glibc's res_init
+const MAX_NAMESERVERS: usize = 3;
+const MAX_NDOTS: u8 = 15;
+const MAX_TIMEOUT: u32 = 30;
+const MAX_ATTEMPTS: u32 = 5;
+
+/// Stub resolver configuration in the format of resolv.conf(5)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvConf {
+    pub nameservers: Vec<IpAddr>,
+    /// Domains to append to names with fewer than `ndots` dots, from `search` or `domain`
+    pub search: Vec<Name>,
+    pub ndots: u8,
+    /// Seconds to wait for a server before trying the next one
+    pub timeout: u32,
+    /// Rounds over the nameservers before giving up
+    pub attempts: u32,
+    pub rotate: bool,
+    pub edns0: bool,
+}
+
+/// The configuration glibc uses when resolv.conf is missing or empty
+impl Default for ResolvConf {
+    fn default() -> Self {
+        Self {
+            nameservers: vec![IpAddr::V4(Ipv4Addr::LOCALHOST)],
+            search: vec![],
+            ndots: 1,
+            timeout: 5,
+            attempts: 2,
+            rotate: false,
+            edns0: false,
+        }
+    }
+}
+
+impl ResolvConf {
+    /// Parses resolv.conf contents the way glibc does, ignoring lines and options it would not
+    /// understand
+    pub fn parse(contents: &str) -> ResolvConf {
+        let mut conf = ResolvConf {
+            nameservers: vec![],
+            ..ResolvConf::default()
+        };
+        for line in contents.lines() {
+            let mut words = line.split_whitespace();
+            match words.next() {
+                Some("nameserver") if conf.nameservers.len() < MAX_NAMESERVERS => {
+                    // a link-local address is unreachable without its scope id, as in
+                    // fe80::1%eth0, which cannot be represented, so such servers are skipped
+                    if let Some(Ok(address)) = words.next().map(str::parse) {
+                        conf.nameservers.push(address);
+                    }
+                }
+                Some("domain") => {
+                    conf.search = words.next().and_then(parse_domain).into_iter().collect();
+                }
+                Some("search") => conf.search = words.filter_map(parse_domain).collect(),
+                Some("options") => words.for_each(|option| conf.apply_option(option)),
+                _ => {}
+            }
+        }
+        if conf.nameservers.is_empty() {
+            conf.nameservers = ResolvConf::default().nameservers;
+        }
+        conf
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<ResolvConf, Error> {
+        std::fs::read_to_string(path).map(|contents| ResolvConf::parse(&contents))
+    }
+
+    /// Reads the system configuration, falling back to the default like glibc does if it is
+    /// missing or cannot be read
+    pub fn system() -> ResolvConf {
+        ResolvConf::from_file(DEFAULT_PATH).unwrap_or_default()
+    }
+
+    fn apply_option(&mut self, option: &str) {
+        match option.split_once(':') {
+            Some(("ndots", value)) => {
+                if let Ok(ndots) = value.parse::<u32>() {
+                    self.ndots = ndots.min(MAX_NDOTS as u32) as u8;
+                }
+            }
+            Some(("timeout", value)) => {
+                if let Ok(timeout) = value.parse::<u32>() {
+                    self.timeout = timeout.clamp(1, MAX_TIMEOUT);
+                }
+            }
+            Some(("attempts", value)) => {
+                if let Ok(attempts) = value.parse::<u32>() {
+                    self.attempts = attempts.clamp(1, MAX_ATTEMPTS);
+                }
+            }
+            None if option == "rotate" => self.rotate = true,
+            None if option == "edns0" => self.edns0 = true,
+            _ => {}
+        }
+    }
+}
+
+/// A search domain, with the root standing for no domain at all
+fn parse_domain(domain: &str) -> Option<Name> {
+    domain
+        .parse::<Name>()
+        .ok()
+        .filter(|domain| !domain.is_root())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv6Addr;
+
+    #[test]
+    fn test_resolv_conf_parse() {
+        let conf = ResolvConf::parse(
+            "# generated by the cluster\n\
+             nameserver 10.96.0.10\n\
+             nameserver fe80::1%eth0\n\
+             nameserver not-an-address\n\
+             nameserver 2001:db8::1\n\
+             search default.svc.cluster.local svc.cluster.local cluster.local\n\
+             ; trailing comment\n\
+             options ndots:5 timeout:2 attempts:9 rotate edns0 unknown:1\n",
+        );
+        assert_eq!(
+            conf,
+            ResolvConf {
+                nameservers: vec![
+                    IpAddr::V4(Ipv4Addr::new(10, 96, 0, 10)),
+                    IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1)),
+                ],
+                search: vec![
+                    "default.svc.cluster.local".parse().unwrap(),
+                    "svc.cluster.local".parse().unwrap(),
+                    "cluster.local".parse().unwrap(),
+                ],
+                ndots: 5,
+                timeout: 2,
+                attempts: 5,
+                rotate: true,
+                edns0: true,
+            }
+        );
+    }
+
+    #[test]
+    fn test_resolv_conf_last_search_or_domain_wins() {
+        let conf = ResolvConf::parse("search a.example b.example\ndomain corp.example\n");
+        assert_eq!(conf.search, vec!["corp.example".parse().unwrap()]);
+        let conf = ResolvConf::parse("domain corp.example\nsearch a.example .\n");
+        assert_eq!(conf.search, vec!["a.example".parse().unwrap()]);
+    }
+
+    #[test]
+    fn test_resolv_conf_defaults() {
+        assert_eq!(ResolvConf::parse(""), ResolvConf::default());
+        let conf = ResolvConf::parse(
+            "nameserver 192.0.2.1\nnameserver 192.0.2.2\nnameserver 192.0.2.3\nnameserver 192.0.2.4\noptions ndots:99 timeout:0\n",
+        );
+        assert_eq!(conf.nameservers.len(), 3);
+        assert_eq!(conf.ndots, 15);
+        assert_eq!(conf.timeout, 1);
+    }
+}
@@ -0,0 +1,76 @@
+use dns_types::Name;
+
+/// The names to query for `name` in the order glibc's res_search tries them: as is first when
+/// it has at least `ndots` dots, then with each search domain appended, then as is if not
+/// tried yet. Absolute names are only tried as is.
+pub fn search_names(name: &Name, absolute: bool, search: &[Name], ndots: u8) -> Vec<Name> {
+    if absolute {
+        return vec![name.clone()];
+    }
+    let as_is_first = name.label_count().saturating_sub(1) >= ndots as usize;
+    let mut names = Vec::with_capacity(search.len() + 1);
+    if as_is_first {
+        names.push(name.clone());
+    }
+    // glibc gives up on a candidate that is too long, skip it instead
+    names.extend(
+        search
+            .iter()
+            .filter_map(|domain| name.append_name(domain).ok()),
+    );
+    if !as_is_first {
+        names.push(name.clone());
+    }
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<Name> {
+        names.iter().map(|name| name.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_search_names_below_ndots() {
+        let search = names(&["default.svc.cluster.local", "svc.cluster.local"]);
+        let (name, absolute) = Name::from_str_partial("api.default").unwrap();
+        assert_eq!(
+            search_names(&name, absolute, &search, 5),
+            names(&[
+                "api.default.default.svc.cluster.local",
+                "api.default.svc.cluster.local",
+                "api.default",
+            ])
+        );
+    }
+
+    #[test]
+    fn test_search_names_at_ndots() {
+        let search = names(&["corp.example"]);
+        let (name, absolute) = Name::from_str_partial("www.example.com").unwrap();
+        assert_eq!(
+            search_names(&name, absolute, &search, 1),
+            names(&["www.example.com", "www.example.com.corp.example"])
+        );
+    }
+
+    #[test]
+    fn test_search_names_absolute() {
+        let search = names(&["corp.example"]);
+        let (name, absolute) = Name::from_str_partial("api.").unwrap();
+        assert_eq!(search_names(&name, absolute, &search, 1), names(&["api"]));
+    }
+
+    #[test]
+    fn test_search_names_skips_too_long() {
+        let long: Name = vec!["a".repeat(63); 3].join(".").parse().unwrap();
+        let search = vec![long, "corp.example".parse().unwrap()];
+        let (name, absolute) = Name::from_str_partial(&"b".repeat(63)).unwrap();
+        assert_eq!(
+            search_names(&name, absolute, &search, 1),
+            vec![name.append_name(&search[1]).unwrap(), name.clone()]
+        );
+    }
+}
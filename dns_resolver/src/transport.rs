@@ -0,0 +1,158 @@
+use std::io::{Error, ErrorKind, Read, Write};
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, TcpStream, UdpSocket};
+use std::time::{Duration, Instant};
+
+/// Sends `request` in a single datagram and returns the first datagram received back that
+/// `is_response` accepts, discarding any others until `timeout` runs out
+pub fn exchange_udp(
+    server: SocketAddr,
+    request: &[u8],
+    receive_buffer_size: usize,
+    timeout: Duration,
+    is_response: impl Fn(&[u8]) -> bool,
+) -> Result<Vec<u8>, Error> {
+    let local = match server {
+        SocketAddr::V4(_) => SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0)),
+        SocketAddr::V6(_) => SocketAddr::from((Ipv6Addr::UNSPECIFIED, 0)),
+    };
+    let socket = UdpSocket::bind(local)?;
+    socket.connect(server)?;
+    socket.send(request)?;
+    let deadline = Instant::now() + timeout;
+    let mut receive_buffer = vec![0u8; receive_buffer_size];
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(timed_out(ErrorKind::TimedOut.into()));
+        }
+        socket.set_read_timeout(Some(remaining))?;
+        let received_size = socket.recv(&mut receive_buffer).map_err(timed_out)?;
+        if is_response(&receive_buffer[..received_size]) {
+            receive_buffer.truncate(received_size);
+            return Ok(receive_buffer);
+        }
+    }
+}
+
+/// Sends `request` over a new TCP connection, framing both messages with a two byte length
+/// prefix (RFC 1035 4.2.2, RFC 7766 8)
+pub fn exchange_tcp(
+    server: SocketAddr,
+    request: &[u8],
+    timeout: Duration,
+) -> Result<Vec<u8>, Error> {
+    let mut stream = TcpStream::connect_timeout(&server, timeout)?;
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))?;
+    stream.write_all(&frame(request)?).map_err(timed_out)?;
+    let mut length = [0u8; 2];
+    stream.read_exact(&mut length).map_err(timed_out)?;
+    let mut response = vec![0u8; u16::from_be_bytes(length) as usize];
+    stream.read_exact(&mut response).map_err(timed_out)?;
+    Ok(response)
+}
+
+/// Socket timeouts surface as `WouldBlock` on Unix, report them uniformly
+fn timed_out(error: Error) -> Error {
+    match error.kind() {
+        ErrorKind::WouldBlock | ErrorKind::TimedOut => Error::new(ErrorKind::TimedOut, "timed out"),
+        _ => error,
+    }
+}
+
+/// Prefixes the message with its length, written together so they can share a segment
+fn frame(message: &[u8]) -> Result<Vec<u8>, Error> {
+    let length = u16::try_from(message.len())
+        .map_err(|_| Error::new(ErrorKind::InvalidInput, "message exceeds 65535 bytes"))?;
+    let mut framed = Vec::with_capacity(message.len() + 2);
+    framed.extend_from_slice(&length.to_be_bytes());
+    framed.extend_from_slice(message);
+    Ok(framed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::thread;
+
+    #[test]
+    fn test_exchange_tcp_length_prefix() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let server = listener.local_addr().unwrap();
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0u8; 5];
+            stream.read_exact(&mut request).unwrap();
+            // reply in two writes to exercise reading a response split across segments
+            stream.write_all(&[0x00, 0x04, 0xbe]).unwrap();
+            stream.write_all(&[0xef, 0xca, 0xfe]).unwrap();
+            request
+        });
+
+        let response = exchange_tcp(server, &[0x01, 0x02, 0x03], Duration::from_secs(5)).unwrap();
+        assert_eq!(response, vec![0xbe, 0xef, 0xca, 0xfe]);
+        assert_eq!(handle.join().unwrap(), [0x00, 0x03, 0x01, 0x02, 0x03]);
+    }
+
+    #[test]
+    fn test_exchange_udp_ipv6() {
+        let socket = UdpSocket::bind("[::1]:0").unwrap();
+        let server = socket.local_addr().unwrap();
+        let handle = thread::spawn(move || {
+            let mut request = [0u8; 3];
+            let (size, peer) = socket.recv_from(&mut request).unwrap();
+            socket.send_to(&[0xbe, 0xef], peer).unwrap();
+            request[..size].to_vec()
+        });
+
+        let response = exchange_udp(
+            server,
+            &[0x01, 0x02, 0x03],
+            512,
+            Duration::from_secs(5),
+            |_| true,
+        );
+        assert_eq!(response.unwrap(), vec![0xbe, 0xef]);
+        assert_eq!(handle.join().unwrap(), vec![0x01, 0x02, 0x03]);
+    }
+
+    #[test]
+    fn test_exchange_udp_discards_unwanted_datagrams() {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let server = socket.local_addr().unwrap();
+        thread::spawn(move || {
+            let mut request = [0u8; 3];
+            let (_, peer) = socket.recv_from(&mut request).unwrap();
+            socket.send_to(&[0xde, 0xad], peer).unwrap();
+            socket.send_to(&[0xbe, 0xef], peer).unwrap();
+            socket.send_to(&[0xde, 0xad], peer).unwrap();
+        });
+
+        let timeout = Duration::from_millis(200);
+        let wanted = |response: &[u8]| response == [0xbe, 0xef];
+        let response = exchange_udp(server, &[0x01, 0x02, 0x03], 512, timeout, wanted);
+        assert_eq!(response.unwrap(), vec![0xbe, 0xef]);
+    }
+
+    #[test]
+    fn test_exchange_udp_times_out_without_wanted_datagram() {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let server = socket.local_addr().unwrap();
+        thread::spawn(move || {
+            let mut request = [0u8; 3];
+            let (_, peer) = socket.recv_from(&mut request).unwrap();
+            socket.send_to(&[0xde, 0xad], peer).unwrap();
+        });
+
+        let timeout = Duration::from_millis(100);
+        let error = exchange_udp(server, &[0x01, 0x02, 0x03], 512, timeout, |_| false);
+        assert_eq!(error.unwrap_err().kind(), ErrorKind::TimedOut);
+    }
+
+    #[test]
+    fn test_frame_too_long() {
+        let error = frame(&vec![0u8; 65536]).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidInput);
+    }
+}
@@ -0,0 +1,245 @@
+use std::{fmt::Display, net::IpAddr};
+
+use crate::{Name, ResourceData, ResourceRecord, ResourceType};
+
+/// EDNS(0) parameters carried by the OPT pseudo-record in the additional section (RFC 6891)
+#[derive(Debug, PartialEq, Clone)]
+pub struct Edns {
+    pub udp_payload_size: u16,
+    pub extended_rcode: u8,
+    pub version: u8,
+    pub dnssec_ok: bool,
+    pub options: Vec<EdnsOption>,
+}
+
+impl Edns {
+    pub fn new(udp_payload_size: u16) -> Self {
+        Self {
+            udp_payload_size,
+            extended_rcode: 0,
+            version: 0,
+            dnssec_ok: false,
+            options: vec![],
+        }
+    }
+
+    /// Reads the EDNS parameters out of an OPT pseudo-record, whose class holds the UDP
+    /// payload size and whose TTL holds the extended rcode, version and flags.
+    pub fn from_resource_record(resource_record: &ResourceRecord) -> Option<Self> {
+        match &resource_record.rdata {
+            ResourceData::OPT(options) => Some(Self {
+                udp_payload_size: resource_record.resource_class.into(),
+                extended_rcode: (resource_record.ttl >> 24) as u8,
+                version: (resource_record.ttl >> 16) as u8,
+                dnssec_ok: resource_record.ttl & 0x8000 != 0,
+                options: options.clone(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl From<Edns> for ResourceRecord {
+    fn from(value: Edns) -> Self {
+        let do_bit = if value.dnssec_ok { 0x8000 } else { 0 };
+        ResourceRecord {
+            name: Name(vec![]),
+            resource_type: ResourceType::OPT,
+            resource_class: value.udp_payload_size.into(),
+            ttl: (value.extended_rcode as u32) << 24 | (value.version as u32) << 16 | do_bit,
+            rdata: ResourceData::OPT(value.options),
+        }
+    }
+}
+
+impl Display for Edns {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "; EDNS: version: {}, flags:", self.version)?;
+        if self.dnssec_ok {
+            write!(f, " do")?;
+        }
+        writeln!(f, "; udp: {}", self.udp_payload_size)?;
+        for option in self.options.as_slice() {
+            writeln!(f, "{}", option)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum EdnsOption {
+    ClientSubnet(ClientSubnet),
+    Cookie(Cookie),
+    Nsid(Nsid),
+    Padding(Padding),
+    ExtendedError(ExtendedError),
+    Unknown { code: u16, data: Vec<u8> },
+}
+
+impl EdnsOption {
+    pub fn code(&self) -> u16 {
+        match self {
+            EdnsOption::Nsid(_) => 3,
+            EdnsOption::ClientSubnet(_) => 8,
+            EdnsOption::Cookie(_) => 10,
+            EdnsOption::Padding(_) => 12,
+            EdnsOption::ExtendedError(_) => 15,
+            EdnsOption::Unknown { code, .. } => *code,
+        }
+    }
+}
+
+impl Display for EdnsOption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EdnsOption::ClientSubnet(client_subnet) => write!(f, "{}", client_subnet),
+            EdnsOption::Cookie(cookie) => write!(f, "{}", cookie),
+            EdnsOption::Nsid(nsid) => write!(f, "{}", nsid),
+            EdnsOption::Padding(padding) => write!(f, "{}", padding),
+            EdnsOption::ExtendedError(extended_error) => write!(f, "{}", extended_error),
+            EdnsOption::Unknown { code, data } => {
+                write!(f, "; OPT={}: ", code)?;
+                write_hex(f, data)
+            }
+        }
+    }
+}
+
+/// EDNS Client Subnet (RFC 7871)
+#[derive(Debug, PartialEq, Clone)]
+pub struct ClientSubnet {
+    pub source_prefix_length: u8,
+    pub scope_prefix_length: u8,
+    /// Only the first `source_prefix_length` bits are significant
+    pub address: IpAddr,
+}
+
+impl ClientSubnet {
+    /// Address family number from the IANA registry
+    pub fn family(&self) -> u16 {
+        match self.address {
+            IpAddr::V4(_) => 1,
+            IpAddr::V6(_) => 2,
+        }
+    }
+}
+
+impl Display for ClientSubnet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "; CLIENT-SUBNET: {}/{}/{}",
+            self.address, self.source_prefix_length, self.scope_prefix_length
+        )
+    }
+}
+
+/// DNS Cookie (RFC 7873), the server cookie is only present once the server has replied
+#[derive(Debug, PartialEq, Clone)]
+pub struct Cookie {
+    pub client: [u8; 8],
+    pub server: Option<Vec<u8>>,
+}
+
+impl Display for Cookie {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "; COOKIE: ")?;
+        write_hex(f, &self.client)?;
+        if let Some(server) = &self.server {
+            write_hex(f, server)?;
+        }
+        Ok(())
+    }
+}
+
+/// Name Server Identifier (RFC 5001), empty in queries
+#[derive(Debug, PartialEq, Clone)]
+pub struct Nsid(pub Vec<u8>);
+
+impl Display for Nsid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "; NSID: ")?;
+        write_hex(f, &self.0)?;
+        write!(f, " (\"")?;
+        for &b in self.0.as_slice() {
+            match b {
+                0x20..=0x7e => write!(f, "{}", b as char)?,
+                _ => write!(f, ".")?,
+            }
+        }
+        write!(f, "\")")
+    }
+}
+
+/// Padding (RFC 7830), the padding octets are always zero
+#[derive(Debug, PartialEq, Clone)]
+pub struct Padding {
+    pub length: u16,
+}
+
+impl Display for Padding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "; PAD: ({} bytes)", self.length)
+    }
+}
+
+/// Extended DNS Error (RFC 8914)
+#[derive(Debug, PartialEq, Clone)]
+pub struct ExtendedError {
+    pub info_code: u16,
+    pub extra_text: String,
+}
+
+impl ExtendedError {
+    pub fn purpose(&self) -> Option<&'static str> {
+        let purpose = match self.info_code {
+            0 => "Other",
+            1 => "Unsupported DNSKEY Algorithm",
+            2 => "Unsupported DS Digest Type",
+            3 => "Stale Answer",
+            4 => "Forged Answer",
+            5 => "DNSSEC Indeterminate",
+            6 => "DNSSEC Bogus",
+            7 => "Signature Expired",
+            8 => "Signature Not Yet Valid",
+            9 => "DNSKEY Missing",
+            10 => "RRSIGs Missing",
+            11 => "No Zone Key Bit Set",
+            12 => "NSEC Missing",
+            13 => "Cached Error",
+            14 => "Not Ready",
+            15 => "Blocked",
+            16 => "Censored",
+            17 => "Filtered",
+            18 => "Prohibited",
+            19 => "Stale NXDOMAIN Answer",
+            20 => "Not Authoritative",
+            21 => "Not Supported",
+            22 => "No Reachable Authority",
+            23 => "Network Error",
+            24 => "Invalid Data",
+            _ => return None,
+        };
+        Some(purpose)
+    }
+}
+
+impl Display for ExtendedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "; EDE: {}", self.info_code)?;
+        if let Some(purpose) = self.purpose() {
+            write!(f, " ({})", purpose)?;
+        }
+        if !self.extra_text.is_empty() {
+            write!(f, ": ({})", self.extra_text)?;
+        }
+        Ok(())
+    }
+}
+
+fn write_hex(f: &mut std::fmt::Formatter<'_>, data: &[u8]) -> std::fmt::Result {
+    for b in data {
+        write!(f, "{:02x}", b)?;
+    }
+    Ok(())
+}
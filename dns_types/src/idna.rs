@@ -0,0 +1,324 @@
+use std::fmt::Display;
+use unicode_general_category::{get_general_category, GeneralCategory};
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+const ACE_PREFIX: &str = "xn--";
+
+// Bootstring parameters for Punycode (RFC 3492 5)
+const BASE: u32 = 36;
+const TMIN: u32 = 1;
+const TMAX: u32 = 26;
+const SKEW: u32 = 38;
+const DAMP: u32 = 700;
+const INITIAL_BIAS: u32 = 72;
+const INITIAL_N: u32 = 128;
+
+#[derive(Debug, PartialEq)]
+pub enum IdnaError {
+    DisallowedCharacter(char),
+    InvalidHyphen,
+    LeadingCombiningMark,
+    Overflow,
+}
+
+impl Display for IdnaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IdnaError::DisallowedCharacter(character) => {
+                write!(f, "character {:?} is not allowed in a label", character)
+            }
+            IdnaError::InvalidHyphen => write!(
+                f,
+                "label must not start or end with a hyphen or have hyphens in positions 3 and 4"
+            ),
+            IdnaError::LeadingCombiningMark => {
+                write!(f, "label must not start with a combining mark")
+            }
+            IdnaError::Overflow => write!(f, "label is too long to encode"),
+        }
+    }
+}
+
+impl std::error::Error for IdnaError {}
+
+/// Converts a Unicode U-label to its `xn--` A-label (RFC 5891 4.4).
+///
+/// The label is first mapped like the NFKC_Casefold step of UTS #46, so compatibility forms
+/// such as fullwidth letters become their plain equivalents, then every code point must be
+/// allowed by IDNA2008 (RFC 5892), which is approximated by its general category.
+pub fn label_to_ascii(label: &str) -> Result<String, IdnaError> {
+    let label: String = label
+        .nfkc()
+        .collect::<String>()
+        .to_lowercase()
+        .nfkc()
+        .collect();
+    if let Some(character) = label.chars().find(|c| !is_allowed(*c)) {
+        return Err(IdnaError::DisallowedCharacter(character));
+    }
+    if label.starts_with(is_combining_mark) {
+        return Err(IdnaError::LeadingCombiningMark);
+    }
+    if label.starts_with('-') || label.ends_with('-') || label.get(2..4) == Some("--") {
+        return Err(IdnaError::InvalidHyphen);
+    }
+    if label.is_ascii() {
+        return Ok(label);
+    }
+    let chars: Vec<char> = label.chars().collect();
+    Ok(format!("{}{}", ACE_PREFIX, punycode_encode(&chars)?))
+}
+
+/// Converts an `xn--` A-label back to its U-label, `None` if `label` is not a valid A-label
+pub fn label_to_unicode(label: &[u8]) -> Option<String> {
+    let label = std::str::from_utf8(label).ok()?;
+    let encoded = label
+        .get(..ACE_PREFIX.len())
+        .filter(|prefix| prefix.eq_ignore_ascii_case(ACE_PREFIX))
+        .map(|_| label[ACE_PREFIX.len()..].to_ascii_lowercase())?;
+    let decoded: String = punycode_decode(&encoded)?.into_iter().collect();
+    // only accept A-labels that would be produced again from the decoded U-label
+    match label_to_ascii(&decoded) {
+        Ok(reencoded) if !decoded.is_ascii() && reencoded.eq_ignore_ascii_case(label) => {
+            Some(decoded)
+        }
+        _ => None,
+    }
+}
+
+/// Letters, digits and marks are allowed (RFC 5892 2.1), as are the ASCII letters, digits and
+/// hyphen (RFC 5892 2.5). Upper case letters left after lowercasing have no stable mapping.
+fn is_allowed(character: char) -> bool {
+    if character.is_ascii() {
+        return character.is_ascii_lowercase() || character.is_ascii_digit() || character == '-';
+    }
+    matches!(
+        get_general_category(character),
+        GeneralCategory::LowercaseLetter
+            | GeneralCategory::OtherLetter
+            | GeneralCategory::ModifierLetter
+            | GeneralCategory::NonspacingMark
+            | GeneralCategory::SpacingMark
+            | GeneralCategory::DecimalNumber
+    )
+}
+
+fn adapt(delta: u32, num_points: u32, first_time: bool) -> u32 {
+    let mut delta = if first_time { delta / DAMP } else { delta / 2 };
+    delta += delta / num_points;
+    let mut k = 0;
+    while delta > ((BASE - TMIN) * TMAX) / 2 {
+        delta /= BASE - TMIN;
+        k += BASE;
+    }
+    k + (BASE - TMIN + 1) * delta / (delta + SKEW)
+}
+
+fn threshold(k: u32, bias: u32) -> u32 {
+    if k <= bias {
+        TMIN
+    } else if k >= bias + TMAX {
+        TMAX
+    } else {
+        k - bias
+    }
+}
+
+fn encode_digit(digit: u32) -> char {
+    match digit {
+        0..=25 => (b'a' + digit as u8) as char,
+        _ => (b'0' + (digit - 26) as u8) as char,
+    }
+}
+
+fn decode_digit(character: u8) -> Option<u32> {
+    match character {
+        b'a'..=b'z' => Some((character - b'a') as u32),
+        b'0'..=b'9' => Some((character - b'0') as u32 + 26),
+        _ => None,
+    }
+}
+
+/// Punycode encoding (RFC 3492 6.3)
+fn punycode_encode(input: &[char]) -> Result<String, IdnaError> {
+    let mut output: String = input.iter().filter(|c| c.is_ascii()).collect();
+    let basic_count = output.len() as u32;
+    if basic_count > 0 {
+        output.push('-');
+    }
+    let mut n = INITIAL_N;
+    let mut delta: u32 = 0;
+    let mut bias = INITIAL_BIAS;
+    let mut handled = basic_count;
+    while (handled as usize) < input.len() {
+        let m = input
+            .iter()
+            .map(|&c| c as u32)
+            .filter(|&c| c >= n)
+            .min()
+            .ok_or(IdnaError::Overflow)?;
+        delta = (m - n)
+            .checked_mul(handled + 1)
+            .and_then(|increment| delta.checked_add(increment))
+            .ok_or(IdnaError::Overflow)?;
+        n = m;
+        for &c in input {
+            let c = c as u32;
+            if c < n {
+                delta = delta.checked_add(1).ok_or(IdnaError::Overflow)?;
+            }
+            if c == n {
+                let mut q = delta;
+                let mut k = BASE;
+                loop {
+                    let t = threshold(k, bias);
+                    if q < t {
+                        break;
+                    }
+                    output.push(encode_digit(t + (q - t) % (BASE - t)));
+                    q = (q - t) / (BASE - t);
+                    k += BASE;
+                }
+                output.push(encode_digit(q));
+                bias = adapt(delta, handled + 1, handled == basic_count);
+                delta = 0;
+                handled += 1;
+            }
+        }
+        delta += 1;
+        n += 1;
+    }
+    Ok(output)
+}
+
+/// Punycode decoding (RFC 3492 6.2)
+fn punycode_decode(input: &str) -> Option<Vec<char>> {
+    let (basic, extended) = match input.rfind('-') {
+        Some(position) => (&input[..position], &input[position + 1..]),
+        None => ("", input),
+    };
+    if !basic.is_ascii() {
+        return None;
+    }
+    let mut output: Vec<char> = basic.chars().collect();
+    let mut n = INITIAL_N;
+    let mut i: u32 = 0;
+    let mut bias = INITIAL_BIAS;
+    let mut digits = extended.bytes().peekable();
+    while digits.peek().is_some() {
+        let old_i = i;
+        let mut w: u32 = 1;
+        let mut k = BASE;
+        loop {
+            let digit = decode_digit(digits.next()?)?;
+            i = i.checked_add(digit.checked_mul(w)?)?;
+            let t = threshold(k, bias);
+            if digit < t {
+                break;
+            }
+            w = w.checked_mul(BASE - t)?;
+            k += BASE;
+        }
+        let length = output.len() as u32 + 1;
+        bias = adapt(i - old_i, length, old_i == 0);
+        n = n.checked_add(i / length)?;
+        i %= length;
+        output.insert(i as usize, char::from_u32(n)?);
+        i += 1;
+    }
+    Some(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_punycode_rfc3492_samples() {
+        // (A) Arabic (Egyptian) and (L) 3<nen>B<gumi><kinpachi><sensei> from RFC 3492 7.1
+        let samples = [
+            (
+                "\u{0644}\u{064A}\u{0647}\u{0645}\u{0627}\u{0628}\u{062A}\u{0643}\u{0644}\u{0645}\u{0648}\u{0634}\u{0639}\u{0631}\u{0628}\u{064A}\u{061F}",
+                "egbpdaj6bu4bxfgehfvwxn",
+            ),
+            (
+                "3\u{5E74}B\u{7D44}\u{91D1}\u{516B}\u{5148}\u{751F}",
+                "3B-ww4c5e180e575a65lsy2b",
+            ),
+            ("bücher", "bcher-kva"),
+        ];
+        for (decoded, encoded) in samples {
+            let chars: Vec<char> = decoded.chars().collect();
+            assert_eq!(punycode_encode(&chars).unwrap(), encoded);
+            assert_eq!(punycode_decode(encoded).unwrap(), chars);
+        }
+    }
+
+    #[test]
+    fn test_label_to_ascii() {
+        assert_eq!(label_to_ascii("bücher"), Ok("xn--bcher-kva".to_string()));
+        assert_eq!(label_to_ascii("BÜCHER"), Ok("xn--bcher-kva".to_string()));
+        assert_eq!(label_to_ascii("münchen"), Ok("xn--mnchen-3ya".to_string()));
+        assert_eq!(label_to_ascii("例え"), Ok("xn--r8jz45g".to_string()));
+        assert_eq!(label_to_ascii("-bücher"), Err(IdnaError::InvalidHyphen));
+        assert_eq!(
+            label_to_ascii("bü cher"),
+            Err(IdnaError::DisallowedCharacter(' '))
+        );
+    }
+
+    #[test]
+    fn test_label_to_ascii_normalizes_to_nfc() {
+        assert_eq!(
+            label_to_ascii("bu\u{0308}cher"),
+            Ok("xn--bcher-kva".to_string())
+        );
+        assert_eq!(
+            label_to_ascii("\u{0308}bucher"),
+            Err(IdnaError::LeadingCombiningMark)
+        );
+    }
+
+    #[test]
+    fn test_label_to_ascii_maps_compatibility_characters() {
+        assert_eq!(
+            label_to_ascii("\u{FF45}\u{FF58}\u{FF41}\u{FF4D}\u{FF50}\u{FF4C}\u{FF45}"),
+            Ok("example".to_string())
+        );
+        assert_eq!(
+            label_to_ascii("\u{FF22}\u{00DC}\u{FF23}\u{FF28}\u{FF25}\u{FF32}"),
+            Ok("xn--bcher-kva".to_string())
+        );
+    }
+
+    #[test]
+    fn test_label_to_ascii_disallowed_code_points() {
+        assert_eq!(
+            label_to_ascii("i\u{2665}bücher"),
+            Err(IdnaError::DisallowedCharacter('\u{2665}'))
+        );
+        assert_eq!(
+            label_to_ascii("\u{1F600}"),
+            Err(IdnaError::DisallowedCharacter('\u{1F600}'))
+        );
+        assert_eq!(
+            label_to_ascii("bücher_"),
+            Err(IdnaError::DisallowedCharacter('_'))
+        );
+    }
+
+    #[test]
+    fn test_label_to_unicode() {
+        assert_eq!(
+            label_to_unicode(b"xn--bcher-kva"),
+            Some("bücher".to_string())
+        );
+        assert_eq!(
+            label_to_unicode(b"XN--BCHER-KVA"),
+            Some("bücher".to_string())
+        );
+        assert_eq!(label_to_unicode(b"example"), None);
+        assert_eq!(label_to_unicode(b"xn--example-"), None);
+        assert_eq!(label_to_unicode(b"xn--99999999999"), None);
+    }
+}
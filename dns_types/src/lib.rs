@@ -1,9 +1,13 @@
+pub mod edns;
+pub mod idna;
 pub mod message;
 pub mod message_header;
 pub mod name;
 pub mod query;
 pub mod resource_record;
 
+pub use edns::*;
+pub use idna::*;
 pub use message::*;
 pub use message_header::*;
 pub use name::*;
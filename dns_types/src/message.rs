@@ -1,26 +1,71 @@
 use std::fmt::Display;
 
-use crate::{message_header::*, query::*, resource_record::*};
+use crate::{edns::*, message_header::*, query::*, resource_record::*};
 
 #[derive(Debug, PartialEq)]
 pub struct Message {
     pub header: MessageHeader,
     pub queries: Vec<Query>,
     pub answers: Vec<ResourceRecord>,
+    pub authorities: Vec<ResourceRecord>,
+    pub additionals: Vec<ResourceRecord>,
+}
+
+impl Message {
+    /// The EDNS parameters of the OPT pseudo-record in the additional section, if any
+    pub fn edns(&self) -> Option<Edns> {
+        self.additionals.iter().find_map(Edns::from_resource_record)
+    }
+
+    /// The response code, extended with the upper bits from the OPT pseudo-record if present
+    pub fn rcode(&self) -> Rcode {
+        match self.edns() {
+            Some(edns) => {
+                let rcode: u16 = self.header.flags.rcode.into();
+                ((edns.extended_rcode as u16) << 4 | rcode & 0b1111).into()
+            }
+            None => self.header.flags.rcode,
+        }
+    }
 }
 
 impl Display for Message {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.header)?;
+        self.header.fmt_with_rcode(f, self.rcode())?;
+        if let Some(edns) = self.edns() {
+            writeln!(f)?;
+            writeln!(f, ";; OPT PSEUDOSECTION:")?;
+            write!(f, "{}", edns)?;
+            writeln!(f)?;
+        }
         writeln!(f, ";; QUESTION SECTION:")?;
         for query in self.queries.as_slice() {
             write!(f, "{}", query)?;
         }
-        writeln!(f, "")?;
+        writeln!(f)?;
         writeln!(f, ";; ANSWER SECTION:")?;
         for answer in self.answers.as_slice() {
             write!(f, "{}", answer)?;
         }
+        if !self.authorities.is_empty() {
+            writeln!(f)?;
+            writeln!(f, ";; AUTHORITY SECTION:")?;
+            for authority in self.authorities.as_slice() {
+                write!(f, "{}", authority)?;
+            }
+        }
+        let additionals: Vec<&ResourceRecord> = self
+            .additionals
+            .iter()
+            .filter(|additional| additional.resource_type != ResourceType::OPT)
+            .collect();
+        if !additionals.is_empty() {
+            writeln!(f)?;
+            writeln!(f, ";; ADDITIONAL SECTION:")?;
+            for additional in additionals {
+                write!(f, "{}", additional)?;
+            }
+        }
         Ok(())
     }
 }
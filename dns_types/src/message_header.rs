@@ -1,4 +1,4 @@
-use std::{convert::Into, fmt::Display};
+use std::fmt::Display;
 
 #[derive(Debug, PartialEq)]
 pub struct MessageHeader {
@@ -10,12 +10,18 @@ pub struct MessageHeader {
     pub additional_count: u16,
 }
 
-impl Display for MessageHeader {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl MessageHeader {
+    /// Writes the header with `rcode` in place of the 4 bit header rcode, so that a message
+    /// can show its extended rcode.
+    pub(crate) fn fmt_with_rcode(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        rcode: Rcode,
+    ) -> std::fmt::Result {
         writeln!(
             f,
             ";; ->>HEADER<<- opcode: {}, rcode: {}, id: {}",
-            self.flags.opcode, self.flags.rcode, self.message_id
+            self.flags.opcode, rcode, self.message_id
         )?;
         writeln!(
             f,
@@ -30,6 +36,12 @@ impl Display for MessageHeader {
     }
 }
 
+impl Display for MessageHeader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_with_rcode(f, self.flags.rcode)
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub struct Flags {
     pub qr: QR,
@@ -38,6 +50,8 @@ pub struct Flags {
     pub truncated: Truncated,
     pub recursion_desired: RecursionDesired,
     pub recursion_available: RecursionAvailable,
+    pub authentic_data: AuthenticData,
+    pub checking_disabled: CheckingDisabled,
     pub rcode: Rcode,
 }
 
@@ -58,6 +72,12 @@ impl Display for Flags {
         if self.recursion_available == RecursionAvailable::Available {
             write!(f, " ra")?;
         }
+        if self.authentic_data == AuthenticData::Authentic {
+            write!(f, " ad")?;
+        }
+        if self.checking_disabled == CheckingDisabled::Disabled {
+            write!(f, " cd")?;
+        }
         Ok(())
     }
 }
@@ -68,45 +88,80 @@ pub enum QR {
     Response,
 }
 
-impl Into<QR> for u8 {
-    fn into(self) -> QR {
-        match self {
+impl From<u8> for QR {
+    fn from(value: u8) -> Self {
+        match value {
             0 => QR::Query,
-            1 => QR::Response,
-            _ => unreachable!(),
+            _ => QR::Response,
         }
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Copy, Clone)]
 pub enum Opcode {
     Query,
     IQuery,
     Status,
-    Unknown,
+    Notify,
+    Update,
+    /// DNS Stateful Operations (RFC 8490)
+    Dso,
+    Unknown(u8),
 }
 
 impl Display for Opcode {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let s = match self {
-            Opcode::Query => "QUERY",
-            Opcode::IQuery => "IQUERY",
-            Opcode::Status => "STATUS",
-            Opcode::Unknown => "UNKNOWN",
-        };
-        write!(f, "{}", s)?;
-        Ok(())
+        match self {
+            Opcode::Query => write!(f, "QUERY"),
+            Opcode::IQuery => write!(f, "IQUERY"),
+            Opcode::Status => write!(f, "STATUS"),
+            Opcode::Notify => write!(f, "NOTIFY"),
+            Opcode::Update => write!(f, "UPDATE"),
+            Opcode::Dso => write!(f, "DSO"),
+            Opcode::Unknown(u) => write!(f, "RESERVED{}", u),
+        }
     }
 }
 
-impl Into<Opcode> for u8 {
-    fn into(self) -> Opcode {
-        match self {
+impl From<u8> for Opcode {
+    fn from(value: u8) -> Self {
+        match value {
             0 => Opcode::Query,
             1 => Opcode::IQuery,
             2 => Opcode::Status,
-            3 => Opcode::Unknown,
-            opcode => unreachable!("unexpected opcode: {}", opcode),
+            4 => Opcode::Notify,
+            5 => Opcode::Update,
+            6 => Opcode::Dso,
+            u => Opcode::Unknown(u),
+        }
+    }
+}
+
+/// The opcode could not be represented in the 4 bits of the message header
+#[derive(Debug, PartialEq)]
+pub struct OpcodeRangeError(pub Opcode);
+
+impl Display for OpcodeRangeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "opcode {} does not fit in 4 bits", self.0)
+    }
+}
+
+impl std::error::Error for OpcodeRangeError {}
+
+impl TryFrom<Opcode> for u8 {
+    type Error = OpcodeRangeError;
+
+    fn try_from(value: Opcode) -> Result<Self, Self::Error> {
+        match value {
+            Opcode::Query => Ok(0),
+            Opcode::IQuery => Ok(1),
+            Opcode::Status => Ok(2),
+            Opcode::Notify => Ok(4),
+            Opcode::Update => Ok(5),
+            Opcode::Dso => Ok(6),
+            Opcode::Unknown(u) if u <= 0b1111 => Ok(u),
+            Opcode::Unknown(_) => Err(OpcodeRangeError(value)),
         }
     }
 }
@@ -117,12 +172,11 @@ pub enum AuthoritativeAnswer {
     NonAuthoritative,
 }
 
-impl Into<AuthoritativeAnswer> for u8 {
-    fn into(self) -> AuthoritativeAnswer {
-        match self {
+impl From<u8> for AuthoritativeAnswer {
+    fn from(value: u8) -> Self {
+        match value {
             0 => AuthoritativeAnswer::NonAuthoritative,
-            1 => AuthoritativeAnswer::Authoritative,
-            _ => unreachable!(),
+            _ => AuthoritativeAnswer::Authoritative,
         }
     }
 }
@@ -133,12 +187,11 @@ pub enum Truncated {
     NotTruncated,
 }
 
-impl Into<Truncated> for u8 {
-    fn into(self) -> Truncated {
-        match self {
+impl From<u8> for Truncated {
+    fn from(value: u8) -> Self {
+        match value {
             0 => Truncated::NotTruncated,
-            1 => Truncated::Truncated,
-            _ => unreachable!(),
+            _ => Truncated::Truncated,
         }
     }
 }
@@ -149,12 +202,11 @@ pub enum RecursionDesired {
     NotDesired,
 }
 
-impl Into<RecursionDesired> for u8 {
-    fn into(self) -> RecursionDesired {
-        match self {
+impl From<u8> for RecursionDesired {
+    fn from(value: u8) -> Self {
+        match value {
             0 => RecursionDesired::NotDesired,
-            1 => RecursionDesired::Desired,
-            _ => unreachable!(),
+            _ => RecursionDesired::Desired,
         }
     }
 }
@@ -165,17 +217,47 @@ pub enum RecursionAvailable {
     NotAvailable,
 }
 
-impl Into<RecursionAvailable> for u8 {
-    fn into(self) -> RecursionAvailable {
-        match self {
+impl From<u8> for RecursionAvailable {
+    fn from(value: u8) -> Self {
+        match value {
             0 => RecursionAvailable::NotAvailable,
-            1 => RecursionAvailable::Available,
-            _ => unreachable!(),
+            _ => RecursionAvailable::Available,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum AuthenticData {
+    Authentic,
+    NotAuthentic,
+}
+
+impl From<u8> for AuthenticData {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => AuthenticData::NotAuthentic,
+            _ => AuthenticData::Authentic,
         }
     }
 }
 
 #[derive(Debug, PartialEq)]
+pub enum CheckingDisabled {
+    Disabled,
+    NotDisabled,
+}
+
+impl From<u8> for CheckingDisabled {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => CheckingDisabled::NotDisabled,
+            _ => CheckingDisabled::Disabled,
+        }
+    }
+}
+
+/// Response code, including the values that only fit with the EDNS extended rcode
+#[derive(Debug, PartialEq, Copy, Clone)]
 pub enum Rcode {
     NoError,
     FormatError,
@@ -183,33 +265,138 @@ pub enum Rcode {
     NameError,
     NotImplemented,
     Refused,
+    YXDomain,
+    YXRRSet,
+    NXRRSet,
+    NotAuth,
+    NotZone,
+    DsoTypeNotImplemented,
+    /// BADVERS, which is also BADSIG in TSIG records (RFC 8945)
+    BadVersion,
+    BadKey,
+    BadTime,
+    BadMode,
+    BadName,
+    BadAlgorithm,
+    BadTruncation,
+    BadCookie,
+    Unknown(u16),
 }
 
 impl Display for Rcode {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let s = match self {
-            Rcode::NoError => "NOERROR",
-            Rcode::FormatError => "FORMATERROR",
-            Rcode::ServerFailure => "SERVERFAILURE",
-            Rcode::NameError => "NAMEERROR",
-            Rcode::NotImplemented => "NOTIMPLEMENTED",
-            Rcode::Refused => "REFUSED",
-        };
-        write!(f, "{}", s)?;
-        Ok(())
+        match self {
+            Rcode::NoError => write!(f, "NOERROR"),
+            Rcode::FormatError => write!(f, "FORMATERROR"),
+            Rcode::ServerFailure => write!(f, "SERVERFAILURE"),
+            Rcode::NameError => write!(f, "NAMEERROR"),
+            Rcode::NotImplemented => write!(f, "NOTIMPLEMENTED"),
+            Rcode::Refused => write!(f, "REFUSED"),
+            Rcode::YXDomain => write!(f, "YXDOMAIN"),
+            Rcode::YXRRSet => write!(f, "YXRRSET"),
+            Rcode::NXRRSet => write!(f, "NXRRSET"),
+            Rcode::NotAuth => write!(f, "NOTAUTH"),
+            Rcode::NotZone => write!(f, "NOTZONE"),
+            Rcode::DsoTypeNotImplemented => write!(f, "DSOTYPENI"),
+            Rcode::BadVersion => write!(f, "BADVERS"),
+            Rcode::BadKey => write!(f, "BADKEY"),
+            Rcode::BadTime => write!(f, "BADTIME"),
+            Rcode::BadMode => write!(f, "BADMODE"),
+            Rcode::BadName => write!(f, "BADNAME"),
+            Rcode::BadAlgorithm => write!(f, "BADALG"),
+            Rcode::BadTruncation => write!(f, "BADTRUNC"),
+            Rcode::BadCookie => write!(f, "BADCOOKIE"),
+            Rcode::Unknown(u) => write!(f, "RCODE{}", u),
+        }
     }
 }
 
-impl Into<Rcode> for u8 {
-    fn into(self) -> Rcode {
-        match self {
+impl From<u16> for Rcode {
+    fn from(value: u16) -> Self {
+        match value {
             0 => Rcode::NoError,
             1 => Rcode::FormatError,
             2 => Rcode::ServerFailure,
             3 => Rcode::NameError,
             4 => Rcode::NotImplemented,
             5 => Rcode::Refused,
-            _ => unreachable!(),
+            6 => Rcode::YXDomain,
+            7 => Rcode::YXRRSet,
+            8 => Rcode::NXRRSet,
+            9 => Rcode::NotAuth,
+            10 => Rcode::NotZone,
+            11 => Rcode::DsoTypeNotImplemented,
+            16 => Rcode::BadVersion,
+            17 => Rcode::BadKey,
+            18 => Rcode::BadTime,
+            19 => Rcode::BadMode,
+            20 => Rcode::BadName,
+            21 => Rcode::BadAlgorithm,
+            22 => Rcode::BadTruncation,
+            23 => Rcode::BadCookie,
+            u => Rcode::Unknown(u),
+        }
+    }
+}
+
+impl From<u8> for Rcode {
+    fn from(value: u8) -> Self {
+        (value as u16).into()
+    }
+}
+
+impl From<Rcode> for u16 {
+    fn from(value: Rcode) -> Self {
+        match value {
+            Rcode::NoError => 0,
+            Rcode::FormatError => 1,
+            Rcode::ServerFailure => 2,
+            Rcode::NameError => 3,
+            Rcode::NotImplemented => 4,
+            Rcode::Refused => 5,
+            Rcode::YXDomain => 6,
+            Rcode::YXRRSet => 7,
+            Rcode::NXRRSet => 8,
+            Rcode::NotAuth => 9,
+            Rcode::NotZone => 10,
+            Rcode::DsoTypeNotImplemented => 11,
+            Rcode::BadVersion => 16,
+            Rcode::BadKey => 17,
+            Rcode::BadTime => 18,
+            Rcode::BadMode => 19,
+            Rcode::BadName => 20,
+            Rcode::BadAlgorithm => 21,
+            Rcode::BadTruncation => 22,
+            Rcode::BadCookie => 23,
+            Rcode::Unknown(u) => u,
+        }
+    }
+}
+
+/// The rcode could not be represented in the 4 bits of the message header
+#[derive(Debug, PartialEq)]
+pub struct ExtendedRcodeError(pub Rcode);
+
+impl Display for ExtendedRcodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "rcode {} needs the EDNS extended rcode to be represented",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for ExtendedRcodeError {}
+
+impl TryFrom<Rcode> for u8 {
+    type Error = ExtendedRcodeError;
+
+    fn try_from(value: Rcode) -> Result<Self, Self::Error> {
+        let rcode: u16 = value.into();
+        if rcode > 0b1111 {
+            return Err(ExtendedRcodeError(value));
         }
+        Ok(rcode as u8)
     }
 }
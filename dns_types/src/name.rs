@@ -1,13 +1,757 @@
-use std::fmt::Display;
+use std::{
+    cmp::Ordering,
+    fmt::Display,
+    hash::{Hash, Hasher},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    str::FromStr,
+};
+
+use crate::idna::{label_to_ascii, label_to_unicode, IdnaError};
+
+/// Maximum length of a single label (RFC 1035 2.3.4)
+pub const MAX_LABEL_LENGTH: usize = 63;
+/// Maximum wire length of a name including the root label (RFC 1035 2.3.4)
+pub const MAX_NAME_LENGTH: usize = 255;
+
+/// A domain name as a list of labels, each an arbitrary sequence of octets.
+///
+/// Names compare, hash and order case-insensitively over ASCII letters as DNS requires, while
+/// the labels keep their original case for display and 0x20 checks.
+///
+/// [`Name::new`] and the methods that build names enforce the label and name length limits,
+/// constructing the tuple directly does not.
+#[derive(Debug, Clone)]
+pub struct Name(pub Vec<Vec<u8>>);
 
 #[derive(Debug, PartialEq)]
-pub struct Name(pub Vec<String>);
+pub enum NameError {
+    EmptyLabel,
+    LabelTooLong(usize),
+    NameTooLong(usize),
+}
+
+impl Display for NameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NameError::EmptyLabel => write!(f, "name contains an empty label"),
+            NameError::LabelTooLong(len) => write!(
+                f,
+                "label of {} octets exceeds {} octets",
+                len, MAX_LABEL_LENGTH
+            ),
+            NameError::NameTooLong(len) => write!(
+                f,
+                "name of {} octets exceeds {} octets",
+                len, MAX_NAME_LENGTH
+            ),
+        }
+    }
+}
+
+impl std::error::Error for NameError {}
+
+#[derive(Debug, PartialEq)]
+pub enum ParseNameError {
+    Empty,
+    EmptyLabel(usize),
+    InvalidEscape(usize),
+    InvalidCharacter { character: char, offset: usize },
+    InvalidIdn { offset: usize, error: IdnaError },
+    LabelTooLong { offset: usize, length: usize },
+    NameTooLong(usize),
+}
+
+impl Display for ParseNameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseNameError::Empty => write!(f, "domain name is empty"),
+            ParseNameError::EmptyLabel(offset) => write!(f, "empty label at offset {}", offset),
+            ParseNameError::InvalidEscape(offset) => {
+                write!(f, "invalid escape sequence at offset {}", offset)
+            }
+            ParseNameError::InvalidCharacter { character, offset } => {
+                write!(
+                    f,
+                    "unexpected character {:?} at offset {}",
+                    character, offset
+                )
+            }
+            ParseNameError::InvalidIdn { offset, error } => {
+                write!(
+                    f,
+                    "invalid internationalized label at offset {}: {}",
+                    offset, error
+                )
+            }
+            ParseNameError::LabelTooLong { offset, length } => write!(
+                f,
+                "label at offset {} is {} octets, exceeding {} octets",
+                offset, length, MAX_LABEL_LENGTH
+            ),
+            ParseNameError::NameTooLong(len) => write!(
+                f,
+                "name of {} octets exceeds {} octets",
+                len, MAX_NAME_LENGTH
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParseNameError {}
+
+impl Name {
+    pub fn new(labels: Vec<Vec<u8>>) -> Result<Self, NameError> {
+        let name = Name(labels);
+        name.validate()?;
+        Ok(name)
+    }
+
+    /// Checks the label and name length limits, which a `Name` built directly from its
+    /// labels may not respect
+    pub fn validate(&self) -> Result<(), NameError> {
+        for label in self.0.as_slice() {
+            if label.is_empty() {
+                return Err(NameError::EmptyLabel);
+            }
+            if label.len() > MAX_LABEL_LENGTH {
+                return Err(NameError::LabelTooLong(label.len()));
+            }
+        }
+        if self.wire_length() > MAX_NAME_LENGTH {
+            return Err(NameError::NameTooLong(self.wire_length()));
+        }
+        Ok(())
+    }
+
+    pub fn root() -> Self {
+        Name(vec![])
+    }
+
+    pub fn labels(&self) -> &[Vec<u8>] {
+        &self.0
+    }
+
+    pub fn label_count(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_root(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Length of the uncompressed wire format, including the root label
+    pub fn wire_length(&self) -> usize {
+        self.0.iter().map(|label| label.len() + 1).sum::<usize>() + 1
+    }
+
+    /// Whether the leftmost label is `*` (RFC 4592)
+    pub fn is_wildcard(&self) -> bool {
+        self.0.first().is_some_and(|label| label == b"*")
+    }
+
+    /// The name with its leftmost label removed, `None` for the root
+    pub fn parent(&self) -> Option<Name> {
+        self.0
+            .split_first()
+            .map(|(_, labels)| Name(labels.to_vec()))
+    }
+
+    /// Whether this name is equal to or below `other`
+    pub fn is_subdomain_of(&self, other: &Name) -> bool {
+        self.0.len() >= other.0.len()
+            && self
+                .0
+                .iter()
+                .rev()
+                .zip(other.0.iter().rev())
+                .all(|(a, b)| a.eq_ignore_ascii_case(b))
+    }
+
+    pub fn prepend_label(&self, label: Vec<u8>) -> Result<Name, NameError> {
+        let mut labels = Vec::with_capacity(self.0.len() + 1);
+        labels.push(label);
+        labels.extend(self.0.iter().cloned());
+        Name::new(labels)
+    }
+
+    /// Appends `suffix` to this name, treating this name as relative to it
+    pub fn append_name(&self, suffix: &Name) -> Result<Name, NameError> {
+        Name::new([self.0.as_slice(), suffix.0.as_slice()].concat())
+    }
+
+    /// The labels of this name before `suffix`, `None` if this name is not below `suffix`
+    pub fn strip_suffix(&self, suffix: &Name) -> Option<Name> {
+        if !self.is_subdomain_of(suffix) {
+            return None;
+        }
+        Some(Name(self.0[..self.0.len() - suffix.0.len()].to_vec()))
+    }
+
+    /// The longest name both names are equal to or below
+    pub fn common_suffix(&self, other: &Name) -> Name {
+        let common = self
+            .0
+            .iter()
+            .rev()
+            .zip(other.0.iter().rev())
+            .take_while(|(a, b)| a.eq_ignore_ascii_case(b))
+            .count();
+        Name(self.0[self.0.len() - common..].to_vec())
+    }
+
+    /// The reverse lookup name of `ip` under `in-addr.arpa` (RFC 1035 3.5) or `ip6.arpa`
+    /// (RFC 3596 2.5)
+    pub fn from_ip(ip: IpAddr) -> Name {
+        let mut labels: Vec<Vec<u8>> = match ip {
+            IpAddr::V4(ip) => ip
+                .octets()
+                .iter()
+                .rev()
+                .map(|octet| octet.to_string().into_bytes())
+                .collect(),
+            IpAddr::V6(ip) => ip
+                .octets()
+                .iter()
+                .rev()
+                .flat_map(|octet| [octet & 0xf, octet >> 4])
+                .map(|nibble| format!("{:x}", nibble).into_bytes())
+                .collect(),
+        };
+        let suffix: &[&[u8]] = match ip {
+            IpAddr::V4(_) => &[b"in-addr", b"arpa"],
+            IpAddr::V6(_) => &[b"ip6", b"arpa"],
+        };
+        labels.extend(suffix.iter().map(|label| label.to_vec()));
+        Name(labels)
+    }
+
+    /// The address a full `in-addr.arpa` or `ip6.arpa` reverse lookup name stands for
+    pub fn to_ip(&self) -> Option<IpAddr> {
+        let (address, suffix) = self.0.split_at(self.0.len().checked_sub(2)?);
+        let is_suffix = |expected: [&[u8]; 2]| {
+            suffix
+                .iter()
+                .zip(expected)
+                .all(|(label, expected)| label.eq_ignore_ascii_case(expected))
+        };
+        if is_suffix([b"in-addr", b"arpa"]) && address.len() == 4 {
+            let mut octets = [0u8; 4];
+            for (octet, label) in octets.iter_mut().rev().zip(address) {
+                if label.is_empty() || label.len() > 3 || !label.iter().all(u8::is_ascii_digit) {
+                    return None;
+                }
+                *octet = std::str::from_utf8(label).ok()?.parse().ok()?;
+            }
+            Some(IpAddr::V4(Ipv4Addr::from(octets)))
+        } else if is_suffix([b"ip6", b"arpa"]) && address.len() == 32 {
+            let mut octets = [0u8; 16];
+            for (i, label) in address.iter().rev().enumerate() {
+                let nibble = match label.as_slice() {
+                    [digit] => (*digit as char).to_digit(16)? as u8,
+                    _ => return None,
+                };
+                octets[i / 2] |= if i % 2 == 0 { nibble << 4 } else { nibble };
+            }
+            Some(IpAddr::V6(Ipv6Addr::from(octets)))
+        } else {
+            None
+        }
+    }
+
+    /// Exact comparison including case, e.g. to verify a 0x20 randomised query name
+    pub fn eq_case_sensitive(&self, other: &Name) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl PartialEq for Name {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.len() == other.0.len()
+            && self
+                .0
+                .iter()
+                .zip(other.0.iter())
+                .all(|(a, b)| a.eq_ignore_ascii_case(b))
+    }
+}
+
+impl Eq for Name {}
+
+impl Hash for Name {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.0.len());
+        for label in self.0.as_slice() {
+            state.write_usize(label.len());
+            for b in label {
+                state.write_u8(b.to_ascii_lowercase());
+            }
+        }
+    }
+}
 
+impl Ord for Name {
+    /// Canonical DNS name order (RFC 4034 6.1): labels are compared from the rightmost as
+    /// lowercase octet strings, and a name sorts before any of its subdomains.
+    fn cmp(&self, other: &Self) -> Ordering {
+        for (a, b) in self.0.iter().rev().zip(other.0.iter().rev()) {
+            let ordering = a
+                .iter()
+                .map(u8::to_ascii_lowercase)
+                .cmp(b.iter().map(u8::to_ascii_lowercase));
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+        }
+        self.0.len().cmp(&other.0.len())
+    }
+}
+
+impl PartialOrd for Name {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// The alternate form `{:#}` renders valid A-labels as Unicode
 impl Display for Name {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for part in self.0.as_slice() {
-            write!(f, "{}.", part)?;
+        if self.0.is_empty() {
+            return write!(f, ".");
+        }
+        for label in self.0.as_slice() {
+            match label_to_unicode(label).filter(|_| f.alternate()) {
+                Some(unicode) => write!(f, "{}", unicode)?,
+                None => write_label(f, label)?,
+            }
+            write!(f, ".")?;
         }
         Ok(())
     }
 }
+
+impl FromStr for Name {
+    type Err = ParseNameError;
+
+    /// Parses a name in master file syntax, treating relative names as absolute. `@` is only
+    /// understood by `from_str_relative`, which has an origin for it to stand for.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Name::from_str_partial(s).map(|(name, _)| name)
+    }
+}
+
+impl Name {
+    /// Parses a name in master file syntax (RFC 1035 5.1), appending `origin` unless the
+    /// name ends with a dot. `@` on its own stands for `origin`, labels with non-ASCII
+    /// characters are converted to `xn--` A-labels.
+    pub fn from_str_relative(s: &str, origin: &Name) -> Result<Name, ParseNameError> {
+        if s == "@" {
+            return Ok(origin.clone());
+        }
+        match Name::from_str_partial(s)? {
+            (name, true) => Ok(name),
+            (name, false) => {
+                let name = Name([name.0, origin.0.clone()].concat());
+                if name.wire_length() > MAX_NAME_LENGTH {
+                    return Err(ParseNameError::NameTooLong(name.wire_length()));
+                }
+                Ok(name)
+            }
+        }
+    }
+
+    /// Parses a possibly relative name without appending an origin, also returning whether
+    /// it ended with a dot
+    pub fn from_str_partial(s: &str) -> Result<(Name, bool), ParseNameError> {
+        let (labels, absolute) = parse_labels(s)?;
+        let name = Name(labels);
+        if name.wire_length() > MAX_NAME_LENGTH {
+            return Err(ParseNameError::NameTooLong(name.wire_length()));
+        }
+        Ok((name, absolute))
+    }
+}
+
+/// Splits `s` into unescaped labels, returning whether the name was absolute
+fn parse_labels(s: &str) -> Result<(Vec<Vec<u8>>, bool), ParseNameError> {
+    if s.is_empty() {
+        return Err(ParseNameError::Empty);
+    }
+    if s == "." {
+        return Ok((vec![], true));
+    }
+    let mut labels = vec![];
+    let mut label = vec![];
+    let mut label_offset = 0;
+    let mut unicode = false;
+    let mut chars = s.char_indices().peekable();
+    while let Some((offset, character)) = chars.next() {
+        match character {
+            // UTS #46 treats the ideographic and fullwidth full stops as label separators
+            '.' | '\u{3002}' | '\u{ff0e}' | '\u{ff61}' => {
+                if label.is_empty() {
+                    return Err(ParseNameError::EmptyLabel(offset));
+                }
+                push_label(
+                    &mut labels,
+                    std::mem::take(&mut label),
+                    std::mem::take(&mut unicode),
+                    label_offset,
+                )?;
+                label_offset = offset + character.len_utf8();
+            }
+            '\\' => match chars.next() {
+                Some((_, digit)) if digit.is_ascii_digit() => {
+                    let mut value = digit as u32 - '0' as u32;
+                    for _ in 0..2 {
+                        match chars.next_if(|(_, digit)| digit.is_ascii_digit()) {
+                            Some((_, digit)) => value = value * 10 + (digit as u32 - '0' as u32),
+                            None => return Err(ParseNameError::InvalidEscape(offset)),
+                        }
+                    }
+                    let octet =
+                        u8::try_from(value).map_err(|_| ParseNameError::InvalidEscape(offset))?;
+                    label.push(octet);
+                }
+                Some((_, escaped)) if escaped.is_ascii() => label.push(escaped as u8),
+                _ => return Err(ParseNameError::InvalidEscape(offset)),
+            },
+            character if character.is_ascii_graphic() => label.push(character as u8),
+            character if !character.is_ascii() && !character.is_whitespace() => {
+                let mut encoded = [0u8; 4];
+                label.extend_from_slice(character.encode_utf8(&mut encoded).as_bytes());
+                unicode = true;
+            }
+            character => return Err(ParseNameError::InvalidCharacter { character, offset }),
+        }
+    }
+    let absolute = label.is_empty();
+    if !absolute {
+        push_label(&mut labels, label, unicode, label_offset)?;
+    }
+    Ok((labels, absolute))
+}
+
+fn push_label(
+    labels: &mut Vec<Vec<u8>>,
+    label: Vec<u8>,
+    unicode: bool,
+    offset: usize,
+) -> Result<(), ParseNameError> {
+    let label = if unicode {
+        let label = String::from_utf8(label).map_err(|_| ParseNameError::InvalidIdn {
+            offset,
+            error: IdnaError::DisallowedCharacter(char::REPLACEMENT_CHARACTER),
+        })?;
+        label_to_ascii(&label)
+            .map_err(|error| ParseNameError::InvalidIdn { offset, error })?
+            .into_bytes()
+    } else {
+        label
+    };
+    if label.len() > MAX_LABEL_LENGTH {
+        return Err(ParseNameError::LabelTooLong {
+            offset,
+            length: label.len(),
+        });
+    }
+    labels.push(label);
+    Ok(())
+}
+
+/// Writes a label in master file format, escaping special characters with a backslash and
+/// non-printable octets as `\DDD` (RFC 1035 5.1)
+fn write_label(f: &mut std::fmt::Formatter<'_>, label: &[u8]) -> std::fmt::Result {
+    for &b in label {
+        match b {
+            b'.' | b'\\' | b'"' | b'(' | b')' | b';' | b'@' | b'$' => write!(f, "\\{}", b as char)?,
+            0x21..=0x7e => write!(f, "{}", b as char)?,
+            _ => write!(f, "\\{:03}", b)?,
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::hash_map::DefaultHasher;
+
+    fn name(labels: &[&str]) -> Name {
+        Name(
+            labels
+                .iter()
+                .map(|label| label.as_bytes().to_vec())
+                .collect(),
+        )
+    }
+
+    fn hash(name: &Name) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        name.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn test_name_case_insensitive_eq_and_hash() {
+        let lower = name(&["example", "com"]);
+        let mixed = name(&["Example", "COM"]);
+        assert_eq!(lower, mixed);
+        assert_eq!(hash(&lower), hash(&mixed));
+        assert!(!lower.eq_case_sensitive(&mixed));
+        assert_eq!(mixed.to_string(), "Example.COM.");
+        assert_ne!(lower, name(&["example", "org"]));
+        assert_ne!(lower, name(&["www", "example", "com"]));
+    }
+
+    #[test]
+    fn test_name_canonical_order() {
+        // example from RFC 4034 6.1
+        let binary = Name(vec![vec![200], b"z".to_vec(), b"example".to_vec()]);
+        let mut names = vec![
+            name(&["z", "example"]),
+            binary.clone(),
+            name(&["*", "z", "example"]),
+            name(&["Z", "a", "example"]),
+            name(&["zABC", "a", "EXAMPLE"]),
+            name(&["yljkjljk", "a", "example"]),
+            name(&["example"]),
+            name(&["a", "example"]),
+        ];
+        names.sort();
+        assert_eq!(
+            names,
+            vec![
+                name(&["example"]),
+                name(&["a", "example"]),
+                name(&["yljkjljk", "a", "example"]),
+                name(&["Z", "a", "example"]),
+                name(&["zABC", "a", "EXAMPLE"]),
+                name(&["z", "example"]),
+                name(&["*", "z", "example"]),
+                binary,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_name_new_limits() {
+        assert_eq!(
+            Name::new(vec![b"www".to_vec(), vec![], b"com".to_vec()]),
+            Err(NameError::EmptyLabel)
+        );
+        assert_eq!(
+            Name::new(vec![vec![b'a'; 64]]),
+            Err(NameError::LabelTooLong(64))
+        );
+        assert_eq!(
+            Name::new(vec![vec![b'a'; 63]; 4]),
+            Err(NameError::NameTooLong(257))
+        );
+        let longest = Name::new(vec![
+            vec![b'a'; 63],
+            vec![b'a'; 63],
+            vec![b'a'; 63],
+            vec![b'a'; 61],
+        ]);
+        assert_eq!(longest.map(|name| name.wire_length()), Ok(255));
+    }
+
+    #[test]
+    fn test_name_manipulation() {
+        let www = name(&["www", "Example", "com"]);
+        let example = name(&["example", "COM"]);
+
+        assert_eq!(www.label_count(), 3);
+        assert!(!www.is_root());
+        assert!(Name::root().is_root());
+        assert_eq!(www.parent(), Some(example.clone()));
+        assert_eq!(Name::root().parent(), None);
+
+        assert!(www.is_subdomain_of(&example));
+        assert!(example.is_subdomain_of(&example));
+        assert!(www.is_subdomain_of(&Name::root()));
+        assert!(!example.is_subdomain_of(&www));
+        assert!(!name(&["example", "org"]).is_subdomain_of(&example));
+
+        assert!(name(&["*", "example", "com"]).is_wildcard());
+        assert!(!www.is_wildcard());
+
+        assert_eq!(example.prepend_label(b"www".to_vec()), Ok(www.clone()));
+        assert_eq!(
+            example.prepend_label(vec![b'a'; 64]),
+            Err(NameError::LabelTooLong(64))
+        );
+        assert_eq!(name(&["www"]).append_name(&example), Ok(www.clone()));
+
+        assert_eq!(www.strip_suffix(&example), Some(name(&["www"])));
+        assert_eq!(example.strip_suffix(&www), None);
+        assert_eq!(
+            www.common_suffix(&name(&["mail", "example", "com"])),
+            example
+        );
+        assert_eq!(www.common_suffix(&name(&["example", "org"])), Name::root());
+    }
+
+    #[test]
+    fn test_name_from_str() {
+        let example = name(&["example", "com"]);
+        assert_eq!("example.com".parse(), Ok(example.clone()));
+        assert_eq!("example.com.".parse(), Ok(example.clone()));
+        assert_eq!(".".parse(), Ok(Name::root()));
+        assert_eq!("@".parse(), Ok(name(&["@"])));
+        assert_eq!(
+            "my-host.example.com".parse(),
+            Ok(name(&["my-host", "example", "com"]))
+        );
+        assert_eq!(
+            "_sip._tcp.example.com".parse(),
+            Ok(name(&["_sip", "_tcp", "example", "com"]))
+        );
+        assert_eq!(
+            r"a\.b\\c\032d.example.com".parse(),
+            Ok(name(&["a.b\\c d", "example", "com"]))
+        );
+        assert_eq!(
+            r"\200.z.example".parse(),
+            Ok(Name(vec![vec![200], b"z".to_vec(), b"example".to_vec()]))
+        );
+
+        let escaped: Name = r"a\.b\040\255.example.com.".parse().unwrap();
+        assert_eq!(escaped.to_string().parse(), Ok(escaped.clone()));
+    }
+
+    #[test]
+    fn test_name_from_str_relative() {
+        let origin = name(&["example", "com"]);
+        assert_eq!(
+            Name::from_str_relative("www", &origin),
+            Ok(name(&["www", "example", "com"]))
+        );
+        assert_eq!(
+            Name::from_str_relative("www.example.org.", &origin),
+            Ok(name(&["www", "example", "org"]))
+        );
+        assert_eq!(Name::from_str_relative("@", &origin), Ok(origin.clone()));
+        assert_eq!(Name::from_str_partial("www"), Ok((name(&["www"]), false)));
+        assert_eq!(Name::from_str_partial("www."), Ok((name(&["www"]), true)));
+        assert_eq!(
+            Name::from_str_partial(r"www\."),
+            Ok((name(&["www."]), false))
+        );
+        assert_eq!(
+            Name::from_str_relative(&format!("{}.", "a".repeat(63)), &origin),
+            Ok(name(&[&"a".repeat(63)]))
+        );
+        let long = vec!["a".repeat(60); 4].join(".");
+        assert_eq!(
+            Name::from_str_relative(&long, &origin),
+            Err(ParseNameError::NameTooLong(257))
+        );
+    }
+
+    #[test]
+    fn test_name_from_str_errors() {
+        assert_eq!("".parse::<Name>(), Err(ParseNameError::Empty));
+        assert_eq!(
+            "example..com".parse::<Name>(),
+            Err(ParseNameError::EmptyLabel(8))
+        );
+        assert_eq!(".com".parse::<Name>(), Err(ParseNameError::EmptyLabel(0)));
+        assert_eq!(
+            "exa mple.com".parse::<Name>(),
+            Err(ParseNameError::InvalidCharacter {
+                character: ' ',
+                offset: 3
+            })
+        );
+        assert_eq!(
+            r"a\25.com".parse::<Name>(),
+            Err(ParseNameError::InvalidEscape(1))
+        );
+        assert_eq!(
+            r"a\256.com".parse::<Name>(),
+            Err(ParseNameError::InvalidEscape(1))
+        );
+        assert_eq!("a\\".parse::<Name>(), Err(ParseNameError::InvalidEscape(1)));
+        assert_eq!(
+            format!("www.{}.com", "a".repeat(64)).parse::<Name>(),
+            Err(ParseNameError::LabelTooLong {
+                offset: 4,
+                length: 64
+            })
+        );
+        assert_eq!(
+            vec!["a".repeat(63); 4].join(".").parse::<Name>(),
+            Err(ParseNameError::NameTooLong(257))
+        );
+    }
+
+    #[test]
+    fn test_name_idna() {
+        let bucher: Name = "Bücher.example".parse().unwrap();
+        assert_eq!(bucher, name(&["xn--bcher-kva", "example"]));
+        assert_eq!(bucher.to_string(), "xn--bcher-kva.example.");
+        assert_eq!(format!("{:#}", bucher), "bücher.example.");
+        assert_eq!(
+            format!("{:#}", name(&["xn--a", "example"])),
+            "xn--a.example."
+        );
+        assert_eq!(
+            "例え。テスト".parse(),
+            Ok(name(&["xn--r8jz45g", "xn--zckzah"]))
+        );
+        assert_eq!(
+            "\u{FF57}\u{FF57}\u{FF57}\u{FF0E}\u{FF45}\u{FF58}\u{FF41}\u{FF4D}\u{FF50}\u{FF4C}\u{FF45}".parse(),
+            Ok(name(&["www", "example"]))
+        );
+        assert_eq!(
+            "ok.-bücher.example".parse::<Name>(),
+            Err(ParseNameError::InvalidIdn {
+                offset: 3,
+                error: IdnaError::InvalidHyphen
+            })
+        );
+        assert_eq!(
+            "bücher\\255.example".parse::<Name>(),
+            Err(ParseNameError::InvalidIdn {
+                offset: 0,
+                error: IdnaError::DisallowedCharacter(char::REPLACEMENT_CHARACTER)
+            })
+        );
+    }
+
+    #[test]
+    fn test_name_reverse_ip() {
+        let v4 = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 10));
+        let v4_name = Name::from_ip(v4);
+        assert_eq!(v4_name.to_string(), "10.2.0.192.in-addr.arpa.");
+        assert_eq!(v4_name.to_ip(), Some(v4));
+
+        let v6: IpAddr = "2001:db8::567:89ab".parse().unwrap();
+        let v6_name = Name::from_ip(v6);
+        assert_eq!(
+            v6_name.to_string(),
+            "b.a.9.8.7.6.5.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6.arpa."
+        );
+        assert_eq!(v6_name.to_ip(), Some(v6));
+        assert_eq!(
+            "B.A.9.8.7.6.5.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.B.D.0.1.0.0.2.IP6.ARPA"
+                .parse::<Name>()
+                .unwrap()
+                .to_ip(),
+            Some(v6)
+        );
+
+        assert_eq!(name(&["2", "0", "192", "in-addr", "arpa"]).to_ip(), None);
+        assert_eq!(
+            name(&["256", "2", "0", "192", "in-addr", "arpa"]).to_ip(),
+            None
+        );
+        assert_eq!(
+            name(&["+1", "2", "0", "192", "in-addr", "arpa"]).to_ip(),
+            None
+        );
+        assert_eq!(name(&["example", "com"]).to_ip(), None);
+        assert_eq!(Name::root().to_ip(), None);
+    }
+}
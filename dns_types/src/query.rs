@@ -1,6 +1,6 @@
-use std::{convert::Into, fmt::Display};
+use std::{fmt::Display, str::FromStr};
 
-use crate::Name;
+use crate::{Name, ParseTypeError, ResourceType};
 
 #[derive(Debug, PartialEq)]
 pub struct Query {
@@ -31,6 +31,9 @@ pub enum QueryType {
     MX,
     SRV,
     AAAA,
+    DNAME,
+    TXT,
+    SPF,
     ANY,
     Unknown(u16),
 }
@@ -47,15 +50,32 @@ impl Display for QueryType {
             QueryType::MX => write!(f, "MX"),
             QueryType::SRV => write!(f, "SRV"),
             QueryType::AAAA => write!(f, "AAAA"),
+            QueryType::DNAME => write!(f, "DNAME"),
+            QueryType::TXT => write!(f, "TXT"),
+            QueryType::SPF => write!(f, "SPF"),
             QueryType::ANY => write!(f, "ANY"),
-            QueryType::Unknown(_) => write!(f, "Unknown"),
+            QueryType::Unknown(u) => write!(f, "TYPE{}", u),
         }
     }
 }
 
-impl Into<QueryType> for u16 {
-    fn into(self) -> QueryType {
-        match self {
+/// Accepts `ANY` and everything `ResourceType` accepts, so `TYPE1` parses as `A` and `OPT` as
+/// the same `TYPE41` it is spelled as for a query
+impl FromStr for QueryType {
+    type Err = ParseTypeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("ANY") {
+            return Ok(QueryType::ANY);
+        }
+        let resource_type: ResourceType = s.parse()?;
+        Ok(u16::from(resource_type).into())
+    }
+}
+
+impl From<u16> for QueryType {
+    fn from(value: u16) -> Self {
+        match value {
             1 => QueryType::A,
             2 => QueryType::NS,
             5 => QueryType::CNAME,
@@ -65,15 +85,18 @@ impl Into<QueryType> for u16 {
             15 => QueryType::MX,
             33 => QueryType::SRV,
             28 => QueryType::AAAA,
+            39 => QueryType::DNAME,
+            16 => QueryType::TXT,
+            99 => QueryType::SPF,
             255 => QueryType::ANY,
             u => QueryType::Unknown(u),
         }
     }
 }
 
-impl Into<u16> for QueryType {
-    fn into(self) -> u16 {
-        match self {
+impl From<QueryType> for u16 {
+    fn from(value: QueryType) -> Self {
+        match value {
             QueryType::A => 1,
             QueryType::NS => 2,
             QueryType::CNAME => 5,
@@ -83,6 +106,9 @@ impl Into<u16> for QueryType {
             QueryType::MX => 15,
             QueryType::SRV => 33,
             QueryType::AAAA => 28,
+            QueryType::DNAME => 39,
+            QueryType::TXT => 16,
+            QueryType::SPF => 99,
             QueryType::ANY => 255,
             QueryType::Unknown(u) => u,
         }
@@ -104,20 +130,38 @@ impl Display for QueryClass {
     }
 }
 
-impl Into<QueryClass> for u16 {
-    fn into(self) -> QueryClass {
-        match self {
+impl From<u16> for QueryClass {
+    fn from(value: u16) -> Self {
+        match value {
             1 => QueryClass::Internet,
             u => QueryClass::Unknown(u),
         }
     }
 }
 
-impl Into<u16> for QueryClass {
-    fn into(self) -> u16 {
-        match self {
+impl From<QueryClass> for u16 {
+    fn from(value: QueryClass) -> Self {
+        match value {
             QueryClass::Internet => 1,
             QueryClass::Unknown(u) => u,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_type_from_str_round_trip() {
+        for value in 0..=u16::MAX {
+            let query_type = QueryType::from(value);
+            assert_eq!(query_type.to_string().parse(), Ok(query_type));
+        }
+        assert_eq!("any".parse(), Ok(QueryType::ANY));
+        assert_eq!("TYPE28".parse(), Ok(QueryType::AAAA));
+        assert_eq!(QueryType::Unknown(65).to_string(), "TYPE65");
+        assert_eq!("OPT".parse(), Ok(QueryType::Unknown(41)));
+        assert_eq!("TYPE41".parse(), Ok(QueryType::Unknown(41)));
+    }
+}
@@ -1,10 +1,10 @@
 use std::{
-    convert::Into,
     fmt::Display,
     net::{Ipv4Addr, Ipv6Addr},
+    str::FromStr,
 };
 
-use crate::Name;
+use crate::{EdnsOption, Name};
 
 #[derive(Debug, PartialEq)]
 pub struct ResourceRecord {
@@ -37,6 +37,10 @@ pub enum ResourceType {
     MX,
     SRV,
     AAAA,
+    DNAME,
+    TXT,
+    SPF,
+    OPT,
     Unknown(u16),
 }
 
@@ -52,14 +56,64 @@ impl Display for ResourceType {
             ResourceType::MX => write!(f, "MX"),
             ResourceType::SRV => write!(f, "SRV"),
             ResourceType::AAAA => write!(f, "AAAA"),
-            ResourceType::Unknown(_) => write!(f, "Unknown"),
+            ResourceType::DNAME => write!(f, "DNAME"),
+            ResourceType::TXT => write!(f, "TXT"),
+            ResourceType::SPF => write!(f, "SPF"),
+            ResourceType::OPT => write!(f, "OPT"),
+            ResourceType::Unknown(u) => write!(f, "TYPE{}", u),
         }
     }
 }
 
-impl Into<ResourceType> for u16 {
-    fn into(self) -> ResourceType {
-        match self {
+#[derive(Debug, PartialEq)]
+pub struct ParseTypeError(pub String);
+
+impl Display for ParseTypeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unknown record type {:?}", self.0)
+    }
+}
+
+impl std::error::Error for ParseTypeError {}
+
+/// Parses the generic `TYPE<n>` mnemonic of RFC 3597 5
+fn parse_generic_type(s: &str) -> Option<u16> {
+    let number = s.get(..4)?.eq_ignore_ascii_case("TYPE").then(|| &s[4..])?;
+    if number.is_empty() || !number.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    number.parse().ok()
+}
+
+/// Accepts mnemonics case-insensitively and `TYPE<n>` for any type, so `TYPE1` parses as `A`
+impl FromStr for ResourceType {
+    type Err = ParseTypeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_uppercase().as_str() {
+            "A" => Ok(ResourceType::A),
+            "NS" => Ok(ResourceType::NS),
+            "CNAME" => Ok(ResourceType::CNAME),
+            "SOA" => Ok(ResourceType::SOA),
+            "WKS" => Ok(ResourceType::WKS),
+            "PTR" => Ok(ResourceType::PTR),
+            "MX" => Ok(ResourceType::MX),
+            "SRV" => Ok(ResourceType::SRV),
+            "AAAA" => Ok(ResourceType::AAAA),
+            "DNAME" => Ok(ResourceType::DNAME),
+            "TXT" => Ok(ResourceType::TXT),
+            "SPF" => Ok(ResourceType::SPF),
+            "OPT" => Ok(ResourceType::OPT),
+            _ => parse_generic_type(s)
+                .map(ResourceType::from)
+                .ok_or_else(|| ParseTypeError(s.to_string())),
+        }
+    }
+}
+
+impl From<u16> for ResourceType {
+    fn from(value: u16) -> Self {
+        match value {
             1 => ResourceType::A,
             2 => ResourceType::NS,
             5 => ResourceType::CNAME,
@@ -69,12 +123,37 @@ impl Into<ResourceType> for u16 {
             15 => ResourceType::MX,
             33 => ResourceType::SRV,
             28 => ResourceType::AAAA,
+            39 => ResourceType::DNAME,
+            16 => ResourceType::TXT,
+            99 => ResourceType::SPF,
+            41 => ResourceType::OPT,
             u => ResourceType::Unknown(u),
         }
     }
 }
 
-#[derive(Debug, PartialEq)]
+impl From<ResourceType> for u16 {
+    fn from(value: ResourceType) -> Self {
+        match value {
+            ResourceType::A => 1,
+            ResourceType::NS => 2,
+            ResourceType::CNAME => 5,
+            ResourceType::SOA => 6,
+            ResourceType::WKS => 11,
+            ResourceType::PTR => 12,
+            ResourceType::MX => 15,
+            ResourceType::SRV => 33,
+            ResourceType::AAAA => 28,
+            ResourceType::DNAME => 39,
+            ResourceType::TXT => 16,
+            ResourceType::SPF => 99,
+            ResourceType::OPT => 41,
+            ResourceType::Unknown(u) => u,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Copy, Clone)]
 pub enum ResourceClass {
     Internet,
     Unknown(u16),
@@ -89,23 +168,60 @@ impl Display for ResourceClass {
     }
 }
 
-impl Into<ResourceClass> for u16 {
-    fn into(self) -> ResourceClass {
-        match self {
+impl From<u16> for ResourceClass {
+    fn from(value: u16) -> Self {
+        match value {
             1 => ResourceClass::Internet,
             u => ResourceClass::Unknown(u),
         }
     }
 }
 
+impl From<ResourceClass> for u16 {
+    fn from(value: ResourceClass) -> Self {
+        match value {
+            ResourceClass::Internet => 1,
+            ResourceClass::Unknown(u) => u,
+        }
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub enum ResourceData {
-    SOA,
-    MX,
+    SOA {
+        mname: Name,
+        rname: Name,
+        serial: u32,
+        refresh: u32,
+        retry: u32,
+        expire: u32,
+        minimum: u32,
+    },
+    MX {
+        preference: u16,
+        exchange: Name,
+    },
+    SRV {
+        priority: u16,
+        weight: u16,
+        port: u16,
+        target: Name,
+    },
     A(Ipv4Addr),
     AAAA(Ipv6Addr),
-    PTR,
-    NS,
+    PTR(Name),
+    NS(Name),
+    CNAME(Name),
+    DNAME(Name),
+    TXT(Vec<Vec<u8>>),
+    SPF(Vec<Vec<u8>>),
+    /// Options of the EDNS OPT pseudo-record, see [`crate::Edns`]
+    OPT(Vec<EdnsOption>),
+    /// Rdata of a type this library does not understand, kept verbatim (RFC 3597)
+    Unknown {
+        rtype: u16,
+        data: Vec<u8>,
+    },
 }
 
 impl Display for ResourceData {
@@ -113,7 +229,95 @@ impl Display for ResourceData {
         match self {
             ResourceData::A(ip) => write!(f, "{}", ip),
             ResourceData::AAAA(ip) => write!(f, "{}", ip),
-            _ => todo!(),
+            ResourceData::PTR(name) => write!(f, "{}", name),
+            ResourceData::NS(name) => write!(f, "{}", name),
+            ResourceData::CNAME(name) => write!(f, "{}", name),
+            ResourceData::DNAME(name) => write!(f, "{}", name),
+            ResourceData::SOA {
+                mname,
+                rname,
+                serial,
+                refresh,
+                retry,
+                expire,
+                minimum,
+            } => write!(
+                f,
+                "{} {} {} {} {} {} {}",
+                mname, rname, serial, refresh, retry, expire, minimum
+            ),
+            ResourceData::MX {
+                preference,
+                exchange,
+            } => write!(f, "{} {}", preference, exchange),
+            ResourceData::SRV {
+                priority,
+                weight,
+                port,
+                target,
+            } => write!(f, "{} {} {} {}", priority, weight, port, target),
+            ResourceData::TXT(strings) => write_character_strings(f, strings),
+            ResourceData::SPF(strings) => write_character_strings(f, strings),
+            ResourceData::OPT(options) => {
+                for option in options {
+                    write!(f, "{} ", option)?;
+                }
+                Ok(())
+            }
+            ResourceData::Unknown { data, .. } => {
+                write!(f, "\\# {}", data.len())?;
+                if !data.is_empty() {
+                    write!(f, " ")?;
+                }
+                for b in data {
+                    write!(f, "{:02x}", b)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Writes each character-string quoted, in zone-file presentation format (RFC 1035 5.1).
+fn write_character_strings(
+    f: &mut std::fmt::Formatter<'_>,
+    strings: &[Vec<u8>],
+) -> std::fmt::Result {
+    for (i, string) in strings.iter().enumerate() {
+        if i > 0 {
+            write!(f, " ")?;
+        }
+        write!(f, "\"")?;
+        for &b in string {
+            match b {
+                b'"' | b'\\' => write!(f, "\\{}", b as char)?,
+                0x20..=0x7e => write!(f, "{}", b as char)?,
+                _ => write!(f, "\\{:03}", b)?,
+            }
+        }
+        write!(f, "\"")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resource_type_from_str_round_trip() {
+        for value in 0..=u16::MAX {
+            let resource_type = ResourceType::from(value);
+            assert_eq!(resource_type.to_string().parse(), Ok(resource_type));
+        }
+        assert_eq!("mx".parse(), Ok(ResourceType::MX));
+        assert_eq!("type1".parse(), Ok(ResourceType::A));
+        assert_eq!("TYPE65".parse(), Ok(ResourceType::Unknown(65)));
+        for invalid in ["", "TYPE", "TYPE65536", "TYPE+1", "BOGUS"] {
+            assert_eq!(
+                invalid.parse::<ResourceType>(),
+                Err(ParseTypeError(invalid.to_string()))
+            );
         }
     }
 }